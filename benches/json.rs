@@ -0,0 +1,78 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use template::json::{
+    parse_json, parse_json_compact, parse_json_string_into, parse_json_with, ParseOptions,
+};
+
+fn string_array(item: &str, n: usize) -> String {
+    format!("[{}]", vec![item; n].join(","))
+}
+
+fn parse_strings(c: &mut Criterion) {
+    let plain = string_array(r#""lorem ipsum dolor sit amet""#, 10_000);
+    let escaped = string_array(r#""lorem \"ipsum\" dolor\nsit amet""#, 10_000);
+
+    c.bench_function("parse plain strings", |b| {
+        b.iter(|| parse_json(black_box(&plain)))
+    });
+    c.bench_function("parse escaped strings", |b| {
+        b.iter(|| parse_json(black_box(&escaped)))
+    });
+}
+
+fn parse_compact(c: &mut Criterion) {
+    let record = r#"{"id":12345,"name":"lorem","tags":["a","b","c"],"score":98.6,"ok":true}"#;
+    let compact = format!("[{}]", vec![record; 10_000].join(","));
+
+    c.bench_function("parse compact document", |b| {
+        b.iter(|| parse_json(black_box(&compact)))
+    });
+    c.bench_function("parse compact document without whitespace", |b| {
+        b.iter(|| parse_json_compact(black_box(&compact)))
+    });
+}
+
+fn parse_strings_into_buffer(c: &mut Criterion) {
+    let literals = vec![r#""lorem \"ipsum\" dolor\nsit amet""#; 10_000];
+
+    c.bench_function("decode strings into new values", |b| {
+        b.iter(|| {
+            for literal in &literals {
+                black_box(parse_json(black_box(literal)).unwrap());
+            }
+        })
+    });
+    c.bench_function("decode strings into a reused buffer", |b| {
+        let mut buf = String::new();
+        b.iter(|| {
+            for literal in &literals {
+                parse_json_string_into(black_box(literal), &mut buf).unwrap();
+                black_box(&buf);
+            }
+        })
+    });
+}
+
+fn parse_records_interning_keys(c: &mut Criterion) {
+    let record = r#"{"id": 12345, "name": "lorem", "email": "lorem@example.com", "active": true}"#;
+    let records = format!("[{}]", vec![record; 10_000].join(", "));
+    let intern = ParseOptions {
+        intern_keys: true,
+        ..Default::default()
+    };
+
+    c.bench_function("parse records", |b| {
+        b.iter(|| parse_json(black_box(&records)))
+    });
+    c.bench_function("parse records interning keys", |b| {
+        b.iter(|| parse_json_with(black_box(&records), intern.clone()))
+    });
+}
+
+criterion_group!(
+    benches,
+    parse_strings,
+    parse_compact,
+    parse_strings_into_buffer,
+    parse_records_interning_keys
+);
+criterion_main!(benches);
@@ -0,0 +1,19 @@
+use anyhow::Result;
+use template::json::parse_json;
+
+fn main() -> Result<()> {
+    let s = r#"{
+      "name": "John Doe",
+      "age": 30,
+      "is_student": false,
+      "marks": [90.0, -80.0, 85.1],
+      "address": {
+        "city": "New York",
+        "zip": 10001
+      }
+    }"#;
+
+    let v = parse_json(s)?;
+    println!("{:#?}", v);
+    Ok(())
+}
@@ -0,0 +1,12 @@
+use anyhow::{anyhow, Result};
+use template::nginx::parse_nginx_log;
+
+// we need to parse:
+// 93.184.216.34 - - [07/Mar/2014:16:05:49 +0800] "GET /api/v1/user/login HTTP/1.1" 200 2 "-" "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_9_4) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/35.0.1916.153 Safari/537.36"
+// with winnow parser combinator
+fn main() -> Result<()> {
+    let s = r#"93.184.216.34 - - [07/Mar/2014:16:05:49 +0800] "GET /api/v1/user/login HTTP/1.1" 200 2 "-" "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_9_4) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/35.0.1916.153 Safari/537.36""#;
+    let log = parse_nginx_log(s).map_err(|e| anyhow!("Failed to parse log: {:?}", e))?;
+    println!("{:?}", log);
+    Ok(())
+}
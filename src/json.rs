@@ -1,145 +1,1260 @@
 use anyhow::{anyhow, Result};
-use std::collections::HashMap;
+use std::{
+    borrow::Cow,
+    collections::{hash_map::Entry, HashMap, HashSet},
+    fmt::{self, Display, Write},
+    str::{FromStr, Utf8Error},
+    sync::Arc,
+};
 use winnow::{
-    ascii::{digit1, multispace0},
-    combinator::{alt, delimited, opt, separated, separated_pair, trace},
-    error::{ContextError, ErrMode, ParserError},
-    stream::{AsChar, Stream, StreamIsPartial},
-    token::take_until,
+    ascii::{digit1, Caseless},
+    combinator::{
+        alt, cut_err, delimited, empty, eof, opt, peek, preceded, separated, separated_pair, trace,
+    },
+    error::{
+        AddContext, ContextError, ErrMode, ParseError, ParserError, StrContext, StrContextValue,
+    },
+    stream::{
+        Accumulate, AsChar, Compare, FindSlice, ParseSlice, Stateful, Stream, StreamIsPartial,
+    },
+    token::{literal, one_of, take_till, take_until, take_while},
     PResult, Parser,
 };
 
+mod array_reader;
+mod borrowed;
+#[cfg(feature = "cbor")]
+mod cbor;
+mod convert;
+mod encoding;
+mod events;
+mod hash;
+mod lines;
+mod path;
+mod sorted;
+mod stats;
+mod tokenizer;
+mod value;
+mod writer;
+
+pub use array_reader::JsonArrayReader;
+pub use borrowed::{parse_json_ref, JsonValueRef};
+pub use convert::FromJson;
+pub use events::{Event, EventIterator};
+pub use lines::JsonLines;
+pub use path::JsonPath;
+pub use sorted::{parse_json_sorted, SortedJsonValue};
+pub use stats::{parse_json_with_stats, ParseStats};
+pub use tokenizer::{Token, Tokenizer};
+pub use value::{IntoIter, Lookup};
+pub use writer::{write_json, write_json_lenient};
+
+/// Arrays and objects nested deeper than this are rejected instead of
+/// overflowing the stack.
+pub const MAX_DEPTH: usize = 128;
+
+/// Streams the JSON parsers can run on, i.e. `&'i str` and `&'i [u8]`.
+trait JsonStream<'i>:
+    Stream<Token: AsChar + Clone, Slice: StrSlice<'i> + ParseSlice<bool>>
+    + StreamIsPartial
+    + Compare<&'static str>
+    + Compare<Caseless<&'static str>>
+    + Compare<char>
+    + FindSlice<&'static str>
+{
+}
+
+impl<'i, I> JsonStream<'i> for I where
+    I: Stream<Token: AsChar + Clone, Slice: StrSlice<'i> + ParseSlice<bool>>
+        + StreamIsPartial
+        + Compare<&'static str>
+        + Compare<Caseless<&'static str>>
+        + Compare<char>
+        + FindSlice<&'static str>
+{
+}
+
+/// A slice of the input that can be viewed as `&'i str` without copying.
+trait StrSlice<'i> {
+    fn to_str(self) -> Result<&'i str, Utf8Error>;
+}
+
+impl<'i> StrSlice<'i> for &'i str {
+    fn to_str(self) -> Result<&'i str, Utf8Error> {
+        Ok(self)
+    }
+}
+
+impl<'i> StrSlice<'i> for &'i [u8] {
+    fn to_str(self) -> Result<&'i str, Utf8Error> {
+        std::str::from_utf8(self)
+    }
+}
+
+type Input<I> = Stateful<I, ParseState>;
+
+fn new_input<'i, I: JsonStream<'i>>(input: I, state: ParseState) -> Input<I> {
+    let len = input.eof_offset();
+    Input {
+        input,
+        state: ParseState { len, ..state },
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct ParseState {
+    options: ParseOptions,
+    depth: usize,
+    // length of the whole document, to turn what's left of it into a position
+    len: usize,
+    // set by `parse_json_compact`: no whitespace is allowed between tokens
+    compact: bool,
+    warnings: Vec<Warning>,
+    // the keys seen so far when interning them
+    keys: HashSet<Arc<str>>,
+    // values parsed so far, checked against `ParseOptions::max_nodes`
+    nodes: usize,
+    // gathered while parsing for `parse_json_with_stats` only
+    stats: Option<ParseStats>,
+    // elements or members of the innermost array or object so far, for the
+    // commas between them
+    elements: usize,
+}
+
+/// A deviation from strict JSON that lenient mode accepted, see
+/// [`parse_json_with_warnings`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warning {
+    /// Byte offset of the deviation in the input.
+    pub offset: usize,
+    pub kind: WarningKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WarningKind {
+    /// Vertical tab or form feed used as whitespace.
+    Whitespace,
+    /// A `+` in front of a number.
+    PlusSign,
+    /// An underscore between the digits of a number.
+    DigitSeparator,
+    /// A fraction without digits before the `.`.
+    MissingIntegerPart,
+    /// `true`, `false` or `null` not written in lowercase.
+    KeywordCase,
+    /// An object key without quotes.
+    UnquotedKey,
+    /// A comma after the last element of an array or object.
+    TrailingComma,
+    /// A `//` line comment or a `/* */` block comment.
+    Comment,
+    /// `NaN`, `Infinity` or `-Infinity` as a number, or a literal too large
+    /// for an `f64`.
+    NonFinite,
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let what = match self.kind {
+            WarningKind::Whitespace => "vertical tab or form feed as whitespace",
+            WarningKind::PlusSign => "leading `+` on a number",
+            WarningKind::DigitSeparator => "underscore in a number",
+            WarningKind::MissingIntegerPart => "number without integer part",
+            WarningKind::KeywordCase => "keyword not in lowercase",
+            WarningKind::UnquotedKey => "unquoted object key",
+            WarningKind::TrailingComma => "trailing comma",
+            WarningKind::Comment => "comment",
+            WarningKind::NonFinite => "non-finite number",
+        };
+        write!(f, "{} at byte {}", what, self.offset)
+    }
+}
+
+fn position<'i, I: JsonStream<'i>>(input: &Input<I>) -> usize {
+    input.state.len - input.eof_offset()
+}
+
+fn warn<'i, I: JsonStream<'i>>(input: &mut Input<I>, offset: usize, kind: WarningKind) {
+    // backtracking may parse the same deviation twice
+    let warning = Warning { offset, kind };
+    if !input.state.warnings.contains(&warning) {
+        input.state.warnings.push(warning);
+    }
+}
+
+/// Controls which inputs beyond RFC 8259 the parser accepts.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Accept common deviations from strict JSON:
+    /// - vertical tab and form feed as insignificant whitespace
+    /// - a leading `+` on numbers
+    /// - underscores between the digits of numbers, e.g. `1_000_000`
+    /// - fractions without an integer part, e.g. `.5` for `0.5`
+    /// - `true`, `false` and `null` in any letter case
+    /// - a comma after the last element of a non-empty array or object
+    /// - unquoted object keys made of ASCII letters, digits, `_` and `$`, not
+    ///   starting with a digit
+    /// - `//` line comments and `/* */` block comments wherever whitespace
+    ///   may appear
+    /// - `NaN`, `Infinity` and `-Infinity` as non-finite floats, as written by
+    ///   [`write_json_lenient`], and literals too large for an `f64`, e.g.
+    ///   `1e999`, as infinite ones
+    pub lenient: bool,
+    /// How numbers are represented in the parsed value.
+    pub number_mode: NumberMode,
+    /// What to do with a key repeated within an object.
+    pub key_policy: KeyPolicy,
+    /// Share one allocation between all equal object keys of the document,
+    /// which saves memory on arrays of records with the same fields.
+    pub intern_keys: bool,
+    /// Fail once the document holds more values than this, counting every
+    /// scalar, array and object at any depth. Together with [`MAX_DEPTH`]
+    /// this bounds the memory spent on untrusted input. `None` means no
+    /// limit.
+    ///
+    /// Only the parsers that take options can set it, i.e. [`parse_json_with`],
+    /// [`parse_json_with_warnings`], [`parse_json_with_stats`] and
+    /// [`parse_json_bytes_with`]; [`parse_json_ref`], [`parse_json_sorted`]
+    /// and the other parsers without options put no limit on the values.
+    pub max_nodes: Option<usize>,
+}
+
+impl ParseOptions {
+    /// Accepts exactly the documents RFC 8259 allows, with every extension
+    /// off; the same as the default options.
+    pub fn strict() -> Self {
+        Self::default()
+    }
+
+    /// Accepts the common deviations listed under
+    /// [`lenient`](Self#structfield.lenient), such as comments and trailing
+    /// commas.
+    pub fn lenient() -> Self {
+        Self {
+            lenient: true,
+            ..Self::default()
+        }
+    }
+}
+
+/// How [`parse_json_with`] resolves repeated keys within an object, which
+/// RFC 8259 leaves up to the implementation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum KeyPolicy {
+    /// The last value of the key wins.
+    #[default]
+    KeepLast,
+    /// The first value of the key wins.
+    KeepFirst,
+    /// A repeated key is a parse error.
+    Error,
+    /// The values of a repeated key are collected into an array, in order,
+    /// e.g. `{"a":1,"a":2}` becomes `{"a":[1,2]}`. Keys that appear once
+    /// keep their value as is.
+    MergeToArray,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NumberMode {
+    /// Numbers without a fraction become [`Num::Int`] when they fit in an
+    /// `i64`, others [`Num::Float`].
+    #[default]
+    Preserve,
+    /// Every number becomes [`Num::Float`].
+    AlwaysFloat,
+    /// Numbers that stand for a whole number within the range of an `i64`
+    /// become [`Num::Int`] whatever their notation, e.g. `1e2` or `1.50e2`,
+    /// others [`Num::Float`].
+    PreferInt,
+}
+
 #[derive(Debug, Clone, PartialEq)]
-enum Num {
+pub enum Num {
     Int(i64),
     Float(f64),
 }
 
-#[allow(unused)]
+impl Num {
+    /// The number as an `i64` if that loses nothing: any integer, and floats
+    /// without a fractional part within the range of an `i64`, e.g. `30.0`.
+    pub fn as_i64_lossless(&self) -> Option<i64> {
+        match *self {
+            Num::Int(n) => Some(n),
+            // `i64::MAX as f64` rounds up to 2^63, which is out of range
+            Num::Float(n) if n.fract() == 0.0 && n >= i64::MIN as f64 && n < i64::MAX as f64 => {
+                Some(n as i64)
+            }
+            Num::Float(_) => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
-enum JsonValue {
+pub enum JsonValue {
     Null,
     Bool(bool),
     Number(Num),
     String(String),
     Array(Vec<JsonValue>),
-    Object(HashMap<String, JsonValue>),
-}
-
-fn main() -> Result<()> {
-    let s = r#"{
-      "name": "John Doe",
-      "age": 30,
-      "is_student": false,
-      "marks": [90.0, -80.0, 85.1],
-      "address": {
-        "city": "New York",
-        "zip": 10001
-      }
-    }"#;
-
-    let v = parse_json(s)?;
-    println!("{:#?}", v);
+    /// Keys are reference counted so that equal keys can share storage, see
+    /// [`ParseOptions::intern_keys`].
+    Object(HashMap<Arc<str>, JsonValue>),
+}
+
+/// The type of a JSON value, without its content.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JsonKind {
+    Null,
+    Bool,
+    Number,
+    String,
+    Array,
+    Object,
+}
+
+/// Floats are written with the shortest digits that parse back to the same
+/// value, or with exactly as many fraction digits as a precision asks for,
+/// e.g. `format!("{:.2}", num)`. Either way they keep a decimal point so that
+/// they parse back as floats.
+///
+/// JSON has no representation for non-finite floats; they are written as the
+/// `NaN`, `Infinity` and `-Infinity` tokens many lenient parsers accept, this
+/// one in [`lenient`](ParseOptions::lenient) mode. Use [`write_json`] to
+/// reject them instead.
+impl Display for Num {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self, f.precision()) {
+            (Num::Int(n), _) => write!(f, "{}", n),
+            (Num::Float(n), _) if n.is_nan() => f.write_str("NaN"),
+            (Num::Float(n), _) if n.is_infinite() => {
+                f.write_str(if *n > 0.0 { "Infinity" } else { "-Infinity" })
+            }
+            (Num::Float(n), Some(0)) if n.is_finite() => write!(f, "{:.0}.0", n),
+            (Num::Float(n), Some(precision)) => write!(f, "{:.*}", precision, n),
+            (Num::Float(n), None) if n.is_finite() && n.fract() == 0.0 => write!(f, "{}.0", n),
+            (Num::Float(n), None) => write!(f, "{}", n),
+        }
+    }
+}
+
+/// Serializes the value as compact JSON. A precision applies to every float in
+/// the value, see [`Num`].
+impl Display for JsonValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JsonValue::Null => f.write_str("null"),
+            JsonValue::Bool(b) => write!(f, "{}", b),
+            JsonValue::Number(n) => Display::fmt(n, f),
+            JsonValue::String(s) => write_string(f, s),
+            JsonValue::Array(values) => {
+                f.write_char('[')?;
+                for (i, v) in values.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(',')?;
+                    }
+                    Display::fmt(v, f)?;
+                }
+                f.write_char(']')
+            }
+            JsonValue::Object(map) => {
+                f.write_char('{')?;
+                for (i, (k, v)) in map.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(',')?;
+                    }
+                    write_string(f, k)?;
+                    f.write_char(':')?;
+                    Display::fmt(v, f)?;
+                }
+                f.write_char('}')
+            }
+        }
+    }
+}
+
+fn write_string(f: &mut impl Write, s: &str) -> fmt::Result {
+    f.write_char('"')?;
+    writer::escape_str(s, |part| f.write_str(part))?;
+    f.write_char('"')
+}
+
+/// Parses a complete JSON document.
+///
+/// As in RFC 8259 the root may be any value, including a bare scalar such as
+/// `42` or `"hello"`.
+///
+/// Insignificant whitespace (spaces, tabs, newlines and carriage returns) is
+/// always trimmed before and after the root, so indented or newline-terminated
+/// documents parse as they are. Anything else after the root, even past
+/// trailing whitespace, is an error, as is a document with no value at all.
+///
+/// A leading byte order mark, U+FEFF, is skipped as well, as
+/// [`parse_json_bytes`] skips a UTF-8 one.
+pub fn parse_json(input: &str) -> Result<JsonValue> {
+    parse_json_with(input, ParseOptions::default())
+}
+
+/// Parses a complete JSON document like [`parse_json`] straight from bytes,
+/// e.g. a memory-mapped file, without first copying them into a `String`.
+/// Strings are checked to be UTF-8 as they are parsed.
+///
+/// A leading byte order mark tells the encoding: a UTF-8 one is skipped, a
+/// UTF-16 one has the document transcoded to UTF-8 first, which needs the
+/// `utf16` feature, and UTF-32 is rejected. Without a mark the input must be
+/// UTF-8.
+pub fn parse_json_bytes(input: &[u8]) -> Result<JsonValue> {
+    parse_json_bytes_with(input, ParseOptions::default())
+}
+
+/// Parses a complete JSON document like [`parse_json_bytes`], with the given
+/// options.
+pub fn parse_json_bytes_with(input: &[u8], options: ParseOptions) -> Result<JsonValue> {
+    let utf8 = encoding::to_utf8(input)?;
+    let (value, _) =
+        parse_root(&*utf8, options.into(), parse_value).map_err(|(_, message)| anyhow!(message))?;
+    Ok(value)
+}
+
+/// Parses a complete JSON document like [`parse_json`], with the given options.
+pub fn parse_json_with(input: &str, options: ParseOptions) -> Result<JsonValue> {
+    let (value, _) =
+        parse_document(input, options.into(), parse_value).map_err(|e| anyhow!(e.message))?;
+    Ok(value)
+}
+
+/// Parses a complete JSON document like [`parse_json_with`], also returning
+/// every deviation from strict JSON that lenient mode let through.
+pub fn parse_json_with_warnings(
+    input: &str,
+    options: ParseOptions,
+) -> Result<(JsonValue, Vec<Warning>)> {
+    let (value, state) =
+        parse_document(input, options.into(), parse_value).map_err(|e| anyhow!(e.message))?;
+    Ok((value, state.warnings))
+}
+
+/// Parses a complete JSON document like [`parse_json`], but only accepts an
+/// object or array as the root, as the older RFC 4627 did.
+pub fn parse_json_strict_root(input: &str) -> Result<JsonValue> {
+    let value = parse_json(input)?;
+    match value.kind() {
+        JsonKind::Object | JsonKind::Array => Ok(value),
+        kind => Err(anyhow!(
+            "Failed to parse JSON: the root must be an object or array, not {:?}",
+            kind
+        )),
+    }
+}
+
+/// Parses a complete JSON document like [`parse_json`], reporting on failure
+/// how far the parser got.
+///
+/// A streaming caller can tell from [`PartialError::rest`] whether the input
+/// merely ended too early (nothing but whitespace left) and more data may
+/// complete it.
+pub fn parse_json_partial(input: &str) -> Result<JsonValue, PartialError<'_>> {
+    let (value, _) = parse_document(input, ParseState::default(), parse_value)?;
+    Ok(value)
+}
+
+/// The error of [`parse_json_partial`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartialError<'a> {
+    /// Length in bytes of the input before the point of failure.
+    pub consumed: usize,
+    /// The input from the point of failure on.
+    pub rest: &'a str,
+    message: String,
+}
+
+impl Display for PartialError<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for PartialError<'_> {}
+
+impl From<ParseOptions> for ParseState {
+    fn from(options: ParseOptions) -> Self {
+        ParseState {
+            options,
+            ..Default::default()
+        }
+    }
+}
+
+// the state is handed back for what was gathered along the way, such as the
+// warnings
+fn parse_document<'i, O>(
+    input: &'i str,
+    state: ParseState,
+    value: impl Parser<Input<&'i str>, O, ContextError>,
+) -> Result<(O, ParseState), PartialError<'i>> {
+    let bom = input
+        .strip_prefix('\u{FEFF}')
+        .map_or(0, |rest| input.len() - rest.len());
+    parse_root(&input[bom..], state, value).map_err(|(consumed, message)| PartialError {
+        consumed: bom + consumed,
+        rest: &input[bom + consumed..],
+        message,
+    })
+}
+
+// the root value with the whitespace around it, as every parser of a whole
+// document reads it; errors come with their offset
+fn parse_root<'i, I, O>(
+    input: I,
+    state: ParseState,
+    value: impl Parser<Input<I>, O, ContextError>,
+) -> Result<(O, ParseState), (usize, String)>
+where
+    I: JsonStream<'i>,
+    ParseError<Input<I>, ContextError>: Display,
+{
+    let mut value = delimited(whitespace, value, whitespace);
+    let mut root = |input: &mut Input<I>| {
+        let value = value.parse_next(input)?;
+        Ok((value, std::mem::take(&mut input.state)))
+    };
+    root.parse(new_input(input, state))
+        .map_err(|e| (e.offset(), format!("Failed to parse JSON: {}", e)))
+}
+
+/// Tells the kind of the root value from its first significant character,
+/// skipping leading whitespace and a byte order mark.
+///
+/// Nothing after that character is looked at, so a `Some` result does not
+/// mean the document is valid; it returns `None` if no value can start there.
+pub fn peek_kind(input: &str) -> Option<JsonKind> {
+    let input = input.strip_prefix('\u{FEFF}').unwrap_or(input);
+    let kind = match input
+        .trim_start_matches([' ', '\t', '\n', '\r'])
+        .chars()
+        .next()?
+    {
+        'n' => JsonKind::Null,
+        't' | 'f' => JsonKind::Bool,
+        '-' | '0'..='9' => JsonKind::Number,
+        '"' => JsonKind::String,
+        '[' => JsonKind::Array,
+        '{' => JsonKind::Object,
+        _ => return None,
+    };
+    Some(kind)
+}
+
+/// Decodes a document consisting of a single JSON string, e.g. `"a\nb"`, into
+/// `buf`, replacing its content.
+///
+/// Reusing one buffer across many calls avoids allocating a fresh `String` for
+/// each of them. On error the content of `buf` is unspecified.
+pub fn parse_json_string_into(input: &str, buf: &mut String) -> Result<()> {
+    buf.clear();
+    let string = |i: &mut Input<&str>| parse_string_into(i, buf);
+    parse_document(input, ParseState::default(), string).map_err(|e| anyhow!(e.message))?;
     Ok(())
 }
 
-fn parse_json(input: &str) -> Result<JsonValue> {
-    let input = &mut (&*input);
-    parse_value(input)
-        .map_err(|e: winnow::error::ErrMode<ContextError>| anyhow!("Failed to parse JSON: {:?}", e))
+/// Parses a complete JSON document that contains no insignificant whitespace
+/// at all, as produced by most serializers in their compact mode.
+///
+/// Skipping the whitespace checks between tokens makes this faster than
+/// [`parse_json`]; any whitespace outside of strings is an error.
+pub fn parse_json_compact(input: &str) -> Result<JsonValue> {
+    let state = ParseState {
+        compact: true,
+        ..Default::default()
+    };
+    let (value, _) = parse_document(input, state, parse_value).map_err(|e| anyhow!(e.message))?;
+    Ok(value)
 }
 
-fn sep_with_space<Input, Output, Error, ParseNext>(
+/// Parses the first `n` elements of an array, e.g. to preview a large
+/// document. Whatever follows them is not looked at, so the array may be
+/// truncated or malformed after its `n`th element. Fewer elements are
+/// returned if the array ends earlier.
+pub fn parse_array_prefix(input: &str, n: usize) -> Result<Vec<JsonValue>> {
+    let mut input = new_input(input, ParseState::default());
+    let elements = |input: &mut Input<&str>| {
+        let mut values = Vec::new();
+        while values.len() < n {
+            let end = if values.is_empty() {
+                opt(peek(']')).parse_next(input)?.is_some()
+            } else {
+                opt(sep_with_space(',')).parse_next(input)?.is_none()
+            };
+            if end {
+                break;
+            }
+            values.push(cut_err(parse_value).parse_next(input)?);
+        }
+        Ok(values)
+    };
+    let ret = preceded((whitespace, '[', whitespace), elements).parse_next(&mut input);
+    ret.map_err(|e| anyhow!("Failed to parse JSON array: {}", e))
+}
+
+/// Skips the whitespace JSON allows between tokens, so that parsers built
+/// around this crate's can treat it the same way. See
+/// [`skip_insignificant_with`] to also skip what lenient mode accepts.
+pub fn skip_insignificant(input: &mut &str) -> PResult<()> {
+    skip_insignificant_with(ParseOptions::default()).parse_next(input)
+}
+
+/// Skips insignificant whitespace, and in lenient mode comments, as the
+/// parser does with the given `options`.
+///
+/// ```
+/// use template::json::{parse_json, skip_insignificant_with, JsonValue, Num, ParseOptions};
+/// use winnow::{ascii::alpha1, combinator::delimited, Parser};
+///
+/// let options = ParseOptions { lenient: true, ..Default::default() };
+/// let skip = || skip_insignificant_with(options.clone());
+/// let mut input = "  /* answer */ value = 42";
+/// let name = delimited(skip(), alpha1, (skip(), '=', skip()))
+///     .parse_next(&mut input)
+///     .unwrap();
+/// assert_eq!(name, "value");
+/// assert_eq!(parse_json(input).unwrap(), JsonValue::Number(Num::Int(42)));
+/// ```
+pub fn skip_insignificant_with<'i>(
+    options: ParseOptions,
+) -> impl Parser<&'i str, (), ContextError> {
+    move |input: &mut &'i str| {
+        let state = ParseState {
+            options: options.clone(),
+            ..Default::default()
+        };
+        let mut stateful = new_input(*input, state);
+        whitespace(&mut stateful)?;
+        *input = stateful.input;
+        Ok(())
+    }
+}
+
+// insignificant whitespace between tokens: space, tab, newline and carriage
+// return, plus vertical tab, form feed and comments in lenient mode
+fn whitespace<'i, I, Error>(input: &mut Input<I>) -> PResult<(), Error>
+where
+    I: JsonStream<'i>,
+    Error: ParserError<Input<I>>,
+{
+    if input.state.compact {
+        return Ok(());
+    }
+    let lenient = input.state.options.lenient;
+    loop {
+        let start = position(input);
+        let space = take_while(0.., move |c: I::Token| match c.as_char() {
+            ' ' | '\t' | '\n' | '\r' => true,
+            '\x0B' | '\x0C' => lenient,
+            _ => false,
+        })
+        .parse_next(input)?;
+        if !lenient {
+            return Ok(());
+        }
+        if space.to_str().is_ok_and(|s| s.contains(['\x0B', '\x0C'])) {
+            warn(input, start, WarningKind::Whitespace);
+        }
+        let start = position(input);
+        let line_comment = ("//", take_till(0.., '\n')).void();
+        let block_comment = ("/*", cut_err((take_until(0.., "*/"), "*/"))).void();
+        if opt(alt((line_comment, block_comment)))
+            .parse_next(input)?
+            .is_none()
+        {
+            return Ok(());
+        }
+        warn(input, start, WarningKind::Comment);
+    }
+}
+
+fn sep_with_space<'i, I, Output, Error, ParseNext>(
     mut parser: ParseNext,
-) -> impl Parser<Input, (), Error>
+) -> impl Parser<Input<I>, (), Error>
 where
-    Input: Stream + StreamIsPartial,
-    <Input as Stream>::Token: AsChar + Clone,
-    Error: ParserError<Input>,
-    ParseNext: Parser<Input, Output, Error>,
+    I: JsonStream<'i>,
+    Error: ParserError<Input<I>>,
+    ParseNext: Parser<Input<I>, Output, Error>,
 {
-    trace("sep_with_space", move |input: &mut Input| {
-        let _ = multispace0.parse_next(input)?;
+    trace("sep_with_space", move |input: &mut Input<I>| {
+        whitespace(input)?;
         parser.parse_next(input)?;
-        multispace0.parse_next(input)?;
+        whitespace(input)?;
         Ok(())
     })
 }
 
-fn parse_null(input: &mut &str) -> PResult<()> {
-    "null".value(()).parse_next(input)
+// track the nesting level so that deeply nested input fails instead of crashing
+fn nested<'i, I, Output, ParseNext>(
+    mut parser: ParseNext,
+) -> impl Parser<Input<I>, Output, ContextError>
+where
+    I: JsonStream<'i>,
+    ParseNext: Parser<Input<I>, Output, ContextError>,
+{
+    trace("nested", move |input: &mut Input<I>| {
+        if input.state.depth >= MAX_DEPTH {
+            let start = input.checkpoint();
+            let err =
+                ContextError::new().add_context(input, &start, StrContext::Label("nesting depth"));
+            return Err(ErrMode::Cut(err));
+        }
+        input.state.depth += 1;
+        if let Some(stats) = &mut input.state.stats {
+            stats.max_depth = stats.max_depth.max(input.state.depth);
+        }
+        let ret = parser.parse_next(input);
+        input.state.depth -= 1;
+        ret
+    })
+}
+
+fn parse_null<'i, I: JsonStream<'i>>(input: &mut Input<I>) -> PResult<()> {
+    if input.state.options.lenient {
+        let start = position(input);
+        let word = Caseless("null")
+            .take()
+            .try_map(StrSlice::to_str)
+            .parse_next(input)?;
+        if word != "null" {
+            warn(input, start, WarningKind::KeywordCase);
+        }
+        Ok(())
+    } else {
+        "null".void().parse_next(input)
+    }
 }
 
-fn parse_bool(input: &mut &str) -> PResult<bool> {
-    alt(("true", "false")).parse_to().parse_next(input)
+fn parse_bool<'i, I: JsonStream<'i>>(input: &mut Input<I>) -> PResult<bool> {
+    if input.state.options.lenient {
+        let start = position(input);
+        let word = alt((Caseless("true"), Caseless("false")))
+            .try_map(StrSlice::to_str)
+            .parse_next(input)?;
+        if word != "true" && word != "false" {
+            warn(input, start, WarningKind::KeywordCase);
+        }
+        Ok(word.eq_ignore_ascii_case("true"))
+    } else {
+        alt(("true", "false")).parse_to().parse_next(input)
+    }
 }
 
-fn parse_num(input: &mut &str) -> PResult<Num> {
-    // process the sign
-    let sign = opt("-").map(|s| s.is_some()).parse_next(input)?;
-    let num = digit1.parse_to::<i64>().parse_next(input)?;
-    let ret: Result<(), ErrMode<ContextError>> = ".".value(()).parse_next(input);
-    if ret.is_ok() {
-        let frac = digit1.parse_to::<i64>().parse_next(input)?;
-        let v = format!("{}.{}", num, frac).parse::<f64>().unwrap();
-        Ok(if sign {
-            Num::Float(-v as _)
+fn parse_num<'i, I: JsonStream<'i>>(input: &mut Input<I>) -> PResult<Num> {
+    // a leading `+` is only valid in lenient mode; the conversions below accept
+    // it as well as `-`
+    let lenient = input.state.options.lenient;
+    // so are the non-finite floats, whatever the number mode
+    if lenient {
+        let start = position(input);
+        let non_finite = alt((
+            "NaN".value(f64::NAN),
+            "Infinity".value(f64::INFINITY),
+            "-Infinity".value(f64::NEG_INFINITY),
+        ));
+        if let Some(n) = opt(non_finite).parse_next(input)? {
+            warn(input, start, WarningKind::NonFinite);
+            return Ok(Num::Float(n));
+        }
+    }
+    let sign = move |c: I::Token| match c.as_char() {
+        '-' => true,
+        '+' => lenient,
+        _ => false,
+    };
+    // underscores between digits are only valid in lenient mode
+    let digits = move |input: &mut Input<I>| {
+        if lenient {
+            separated(1.., digit1, '_').parse_next(input)
         } else {
-            Num::Float(v as _)
-        })
+            digit1.void().parse_next(input)
+        }
+    };
+    // take the whole literal so the conversion sees the exact digits, e.g. the
+    // leading zeros of a fraction
+    let start = position(input);
+    // so is leaving out the integer part of a fraction, as in `.5`
+    let int_part = move |input: &mut Input<I>| {
+        if lenient {
+            opt(digits).void().parse_next(input)
+        } else {
+            digits(input)
+        }
+    };
+    let exponent = |input: &mut Input<I>| {
+        (one_of(['e', 'E']), opt(one_of(['+', '-'])), digit1)
+            .void()
+            .parse_next(input)
+    };
+    let (num, literal) = alt((
+        (opt(one_of(sign)), int_part, '.', digits, opt(exponent))
+            .take()
+            .verify_map(to_number)
+            .map(Num::Float),
+        (opt(one_of(sign)), digits, exponent)
+            .take()
+            .verify_map(to_number)
+            .map(Num::Float),
+        // integers out of the range of an `i64` fall back to a float
+        (opt(one_of(sign)), digits)
+            .take()
+            .verify_map(|literal: I::Slice| {
+                let literal = literal.to_str().ok()?;
+                to_number(literal)
+                    .map(Num::Int)
+                    .or_else(|| to_number(literal).map(Num::Float))
+            }),
+    ))
+    // literals too large for an `f64`, e.g. `1e999`, read as infinite
+    .verify(|num: &Num| lenient || !matches!(num, Num::Float(n) if !n.is_finite()))
+    .with_taken()
+    .parse_next(input)?;
+    let literal = literal.to_str().unwrap_or_default();
+    if lenient {
+        if literal.starts_with('+') {
+            warn(input, start, WarningKind::PlusSign);
+        }
+        if let Some(i) = literal.find('_') {
+            warn(input, start + i, WarningKind::DigitSeparator);
+        }
+        if let Some(i) = literal
+            .find('.')
+            .filter(|&i| !literal[..i].ends_with(|c: char| c.is_ascii_digit()))
+        {
+            warn(input, start + i, WarningKind::MissingIntegerPart);
+        }
+        if matches!(num, Num::Float(n) if !n.is_finite()) {
+            warn(input, start, WarningKind::NonFinite);
+        }
+    }
+    Ok(match (input.state.options.number_mode, num) {
+        (NumberMode::AlwaysFloat, Num::Int(n)) => Num::Float(n as f64),
+        (NumberMode::PreferInt, Num::Float(n)) => {
+            to_integral(literal).map_or(Num::Float(n), Num::Int)
+        }
+        (_, num) => num,
+    })
+}
+
+// the whole number a literal such as `1.5e3` stands for, worked out on its
+// digits rather than the `f64` so that large integers keep all of them
+fn to_integral(literal: &str) -> Option<i64> {
+    let literal = literal.replace('_', "");
+    let (mantissa, exp) = match literal.split_once(['e', 'E']) {
+        Some((mantissa, exp)) => (mantissa, exp.parse::<i32>().ok()?),
+        None => (literal.as_str(), 0),
+    };
+    let (negative, mantissa) = match mantissa.strip_prefix('-') {
+        Some(mantissa) => (true, mantissa),
+        None => (false, mantissa.trim_start_matches('+')),
+    };
+    let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let digits = format!("{}{}", int, frac);
+    let exp = exp.checked_sub(i32::try_from(frac.len()).ok()?)?;
+    let value = if exp < 0 {
+        // the digits moved behind the point must all be zeros
+        let (whole, dropped) =
+            digits.split_at(digits.len().saturating_sub(exp.unsigned_abs() as usize));
+        if dropped.contains(|c| c != '0') {
+            return None;
+        }
+        whole.parse::<i128>().unwrap_or(0)
     } else {
-        Ok(if sign {
-            Num::Int(-(num))
+        let value = digits.parse::<i128>().ok()?;
+        if value == 0 {
+            0
         } else {
-            Num::Int(num)
-        })
+            value.checked_mul(10i128.checked_pow(exp.unsigned_abs())?)?
+        }
+    };
+    i64::try_from(if negative { -value } else { value }).ok()
+}
+
+fn to_number<'i, T: FromStr>(literal: impl StrSlice<'i>) -> Option<T> {
+    let s = literal.to_str().ok()?;
+    if s.contains('_') {
+        s.replace('_', "").parse().ok()
+    } else {
+        s.parse().ok()
     }
 }
 
-// json allows quoted strings to have escaped characters, we won't handle that here
-fn parse_string(input: &mut &str) -> PResult<String> {
-    let ret = delimited('"', take_until(0.., '"'), '"').parse_next(input)?;
-    Ok(ret.to_string())
+// borrow the string straight from the input unless an escape forces a copy
+fn parse_string<'i, I: JsonStream<'i>>(input: &mut I) -> PResult<Cow<'i, str>> {
+    '"'.parse_next(input)?;
+    let head = parse_unescaped(input)?;
+    let ret = if opt(peek('\\')).parse_next(input)?.is_some() {
+        let mut s = head.to_owned();
+        parse_escaped(input, &mut s)?;
+        Cow::Owned(s)
+    } else {
+        Cow::Borrowed(head)
+    };
+    '"'.parse_next(input)?;
+    Ok(ret)
 }
 
-fn parse_array(input: &mut &str) -> PResult<Vec<JsonValue>> {
-    let sep1 = sep_with_space('[');
-    let sep2 = sep_with_space(']');
-    let sep_comma = sep_with_space(',');
-    let parse_values = separated(0.., parse_value, sep_comma);
-    delimited(sep1, parse_values, sep2).parse_next(input)
+fn parse_string_into<'i, I: JsonStream<'i>>(input: &mut I, buf: &mut String) -> PResult<()> {
+    '"'.parse_next(input)?;
+    buf.push_str(parse_unescaped(input)?);
+    parse_escaped(input, buf)?;
+    '"'.parse_next(input)?;
+    Ok(())
 }
 
-fn parse_object(input: &mut &str) -> PResult<HashMap<String, JsonValue>> {
-    let sep1 = sep_with_space('{');
-    let sep2 = sep_with_space('}');
-    let sep_comma = sep_with_space(',');
-    let sep_colon = sep_with_space(':');
-    let parse_kv_pair = separated_pair(parse_string, sep_colon, parse_value);
-    let parse_kv = separated(1.., parse_kv_pair, sep_comma);
-    delimited(sep1, parse_kv, sep2).parse_next(input)
+// the rest of a string from its first escape on, decoded into `buf`
+fn parse_escaped<'i, I: JsonStream<'i>>(input: &mut I, buf: &mut String) -> PResult<()> {
+    while let Some(c) = opt(parse_escape).parse_next(input)? {
+        buf.push(c);
+        buf.push_str(parse_unescaped(input)?);
+    }
+    Ok(())
+}
+
+fn parse_unescaped<'i, I: JsonStream<'i>>(input: &mut I) -> PResult<&'i str> {
+    take_till(0.., ['"', '\\'])
+        .try_map(StrSlice::to_str)
+        .parse_next(input)
+}
+
+fn parse_escape<'i, I: JsonStream<'i>>(input: &mut I) -> PResult<char> {
+    let escaped = alt((
+        '"'.value('"'),
+        '\\'.value('\\'),
+        '/'.value('/'),
+        'b'.value('\u{8}'),
+        'f'.value('\u{c}'),
+        'n'.value('\n'),
+        'r'.value('\r'),
+        't'.value('\t'),
+        preceded('u', parse_unicode),
+    ));
+    preceded('\\', escaped).parse_next(input)
+}
+
+// \uXXXX, where characters outside the BMP are written as a surrogate pair
+fn parse_unicode<'i, I: JsonStream<'i>>(input: &mut I) -> PResult<char> {
+    let high = parse_hex4(input)?;
+    let code = if (0xD800..0xDC00).contains(&high) {
+        let low = preceded(
+            "\\u",
+            parse_hex4.verify(|low| (0xDC00..0xE000).contains(low)),
+        )
+        .parse_next(input)?;
+        0x10000 + ((high as u32 - 0xD800) << 10) + (low as u32 - 0xDC00)
+    } else {
+        high as u32
+    };
+    // a lone low surrogate is not a valid char
+    char::from_u32(code).ok_or_else(|| ErrMode::Backtrack(ContextError::new()))
+}
+
+fn parse_hex4<'i, I: JsonStream<'i>>(input: &mut I) -> PResult<u16> {
+    take_while(4, AsChar::is_hex_digit)
+        .verify_map(|s: I::Slice| u16::from_str_radix(s.to_str().ok()?, 16).ok())
+        .parse_next(input)
+}
+
+fn parse_array<'i, I: JsonStream<'i>>(input: &mut Input<I>) -> PResult<Vec<JsonValue>> {
+    array_of(parse_value).parse_next(input)
+}
+
+fn parse_object<'i, I: JsonStream<'i>>(
+    input: &mut Input<I>,
+) -> PResult<HashMap<Arc<str>, JsonValue>> {
+    let policy = input.state.options.key_policy;
+    if policy == KeyPolicy::KeepLast {
+        return object_of(parse_owned_key, parse_value).parse_next(input);
+    }
+    let entries: Vec<(Arc<str>, JsonValue)> =
+        object_of(parse_owned_key, parse_value).parse_next(input)?;
+    let mut map = HashMap::with_capacity(entries.len());
+    // keys whose value already is the array of their merged values
+    let mut merged = HashSet::new();
+    for (key, value) in entries {
+        let mut entry = match map.entry(key) {
+            Entry::Occupied(entry) => entry,
+            Entry::Vacant(entry) => {
+                entry.insert(value);
+                continue;
+            }
+        };
+        match policy {
+            KeyPolicy::KeepLast => {
+                entry.insert(value);
+            }
+            KeyPolicy::KeepFirst => {}
+            KeyPolicy::Error => return fail_with(input, DuplicateKey(entry.key().to_string())),
+            KeyPolicy::MergeToArray => {
+                if merged.insert(entry.key().clone()) {
+                    let first = std::mem::replace(entry.get_mut(), JsonValue::Null);
+                    entry.insert(JsonValue::Array(vec![first]));
+                }
+                if let JsonValue::Array(values) = entry.get_mut() {
+                    values.push(value);
+                }
+            }
+        }
+    }
+    Ok(map)
+}
+
+fn parse_owned_key<'i, I: JsonStream<'i>>(input: &mut Input<I>) -> PResult<Arc<str>> {
+    let key = parse_key(input)?;
+    if !input.state.options.intern_keys {
+        return Ok(Arc::from(key));
+    }
+    if let Some(key) = input.state.keys.get(key.as_ref()) {
+        return Ok(key.clone());
+    }
+    let key = Arc::from(key);
+    input.state.keys.insert(Arc::clone(&key));
+    Ok(key)
+}
+
+fn parse_key<'i, I: JsonStream<'i>>(input: &mut Input<I>) -> PResult<Cow<'i, str>> {
+    if input.state.options.lenient {
+        let start = position(input);
+        if let Some(key) = opt(parse_identifier).parse_next(input)? {
+            warn(input, start, WarningKind::UnquotedKey);
+            return Ok(Cow::Borrowed(key));
+        }
+    }
+    parse_string(input)
+}
+
+// an unquoted key as in JavaScript, restricted to ASCII
+fn parse_identifier<'i, I: JsonStream<'i>>(input: &mut I) -> PResult<&'i str> {
+    let start = |c: I::Token| matches!(c.as_char(), 'a'..='z' | 'A'..='Z' | '_' | '$');
+    let rest = |c: I::Token| matches!(c.as_char(), 'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '$');
+    (one_of(start), take_while(0.., rest))
+        .take()
+        .try_map(StrSlice::to_str)
+        .parse_next(input)
 }
 
-fn parse_value(input: &mut &str) -> PResult<JsonValue> {
-    alt((
+fn parse_value<'i, I: JsonStream<'i>>(input: &mut Input<I>) -> PResult<JsonValue> {
+    let value = alt((
         parse_null.value(JsonValue::Null),
         parse_bool.map(JsonValue::Bool),
         parse_num.map(JsonValue::Number),
-        parse_string.map(JsonValue::String),
+        parse_string.map(|s| JsonValue::String(s.into_owned())),
         parse_array.map(JsonValue::Array),
         parse_object.map(JsonValue::Object),
     ))
-    .parse_next(input)
+    .context(EXPECTED_VALUE)
+    .parse_next(input)?;
+    count_node(input)?;
+    if let Some(stats) = &mut input.state.stats {
+        // arrays and objects count their punctuation in `container`
+        if !matches!(value, JsonValue::Array(_) | JsonValue::Object(_)) {
+            stats.tokens += 1;
+        }
+    }
+    Ok(value)
+}
+
+// values are counted once parsed, so that a failed alternative is not
+fn count_node<'i, I: JsonStream<'i>>(input: &mut Input<I>) -> PResult<()> {
+    input.state.nodes += 1;
+    if input
+        .state
+        .options
+        .max_nodes
+        .is_some_and(|max| input.state.nodes > max)
+    {
+        let start = input.checkpoint();
+        let err = ContextError::new().add_context(input, &start, StrContext::Label("node count"));
+        return Err(ErrMode::Cut(err));
+    }
+    Ok(())
+}
+
+const EXPECTED_VALUE: StrContext = StrContext::Expected(StrContextValue::Description(
+    "one of: null, true/false, number, string, array, object",
+));
+
+// the array syntax, shared by every kind of value tree
+fn array_of<'i, I, Value, ParseValue>(
+    parse_value: ParseValue,
+) -> impl Parser<Input<I>, Vec<Value>, ContextError>
+where
+    I: JsonStream<'i>,
+    ParseValue: Parser<Input<I>, Value, ContextError>,
+{
+    let sep_comma = sep_with_space(',');
+    let parse_values = separated(0.., element(parse_value, 0), sep_comma);
+    container(('[', ']'), "array", parse_values)
+}
+
+// the object syntax, shared by every kind of value tree
+fn object_of<'i, I, Key, Value, Map, ParseKey, ParseValue>(
+    parse_key: ParseKey,
+    parse_value: ParseValue,
+) -> impl Parser<Input<I>, Map, ContextError>
+where
+    I: JsonStream<'i>,
+    Map: Accumulate<(Key, Value)>,
+    ParseKey: Parser<Input<I>, Key, ContextError>,
+    ParseValue: Parser<Input<I>, Value, ContextError>,
+{
+    let sep_comma = sep_with_space(',');
+    let sep_colon = |input: &mut Input<I>| parse_colon(input);
+    let parse_kv_pair = separated_pair(parse_key, sep_colon, parse_value);
+    // the key and the colon
+    let parse_kv = separated(0.., element(parse_kv_pair, 2), sep_comma);
+    container(('{', '}'), "object", parse_kv)
+}
+
+// an element of an array or member of an object, counted once parsed for
+// `ParseStats`: separators are not, as a trailing comma is backtracked over
+fn element<'i, I, Output, ParseNext>(
+    mut parser: ParseNext,
+    tokens: usize,
+) -> impl Parser<Input<I>, Output, ContextError>
+where
+    I: JsonStream<'i>,
+    ParseNext: Parser<Input<I>, Output, ContextError>,
+{
+    move |input: &mut Input<I>| {
+        let ret = parser.parse_next(input)?;
+        if let Some(stats) = &mut input.state.stats {
+            stats.tokens += tokens;
+            input.state.elements += 1;
+        }
+        Ok(ret)
+    }
+}
+
+// `content` between a pair of brackets; running out of input before the
+// closing one is reported along with where the opening one was
+fn container<'i, I, Output, ParseNext>(
+    (open, close): (char, char),
+    name: &'static str,
+    mut content: ParseNext,
+) -> impl Parser<Input<I>, Output, ContextError>
+where
+    I: JsonStream<'i>,
+    ParseNext: Parser<Input<I>, Output, ContextError>,
+{
+    trace(name, move |input: &mut Input<I>| {
+        whitespace(input)?;
+        let at = position(input);
+        literal(open).parse_next(input)?;
+        whitespace(input)?;
+        let content_start = position(input);
+        let outer = std::mem::take(&mut input.state.elements);
+        let ret = nested(content.by_ref()).parse_next(input)?;
+        let elements = std::mem::replace(&mut input.state.elements, outer);
+        if let Some(stats) = &mut input.state.stats {
+            // the brackets and the commas between elements
+            stats.tokens += 2 + elements.saturating_sub(1);
+        }
+        // lenient mode allows a comma after the last element, but not on its own
+        if input.state.options.lenient && position(input) > content_start {
+            let comma = position(input);
+            if opt(sep_with_space(',')).parse_next(input)?.is_some() {
+                warn(input, comma, WarningKind::TrailingComma);
+            }
+        }
+        if opt(sep_with_space(close)).parse_next(input)?.is_some() {
+            return Ok(ret);
+        }
+        let truncated = opt(peek((opt(','), whitespace, eof))).parse_next(input)?;
+        if truncated.is_none() {
+            // something else follows; let the usual error describe it
+            sep_with_space(close).parse_next(input)?;
+            return Ok(ret);
+        }
+        let err = Unterminated {
+            name,
+            open,
+            close,
+            at,
+        };
+        fail_with(input, err)
+    })
+}
+
+// exactly one colon between a key and its value
+fn parse_colon<'i, I: JsonStream<'i>>(input: &mut Input<I>) -> PResult<()> {
+    whitespace(input)?;
+    if opt(':').parse_next(input)?.is_none() {
+        return fail_with(input, SyntaxError("expected `:` after key"));
+    }
+    whitespace(input)?;
+    if opt(peek(':')).parse_next(input)?.is_some() {
+        return fail_with(input, SyntaxError("unexpected `:`"));
+    }
+    Ok(())
+}
+
+// a cut error at the current position, described by `err`
+fn fail_with<'i, I, Output, E>(input: &mut Input<I>, err: E) -> PResult<Output>
+where
+    I: JsonStream<'i>,
+    E: std::error::Error + Clone + Send + Sync + 'static,
+{
+    cut_err(empty.try_map(move |_| Err::<Output, _>(err.clone()))).parse_next(input)
+}
+
+#[derive(Debug, Clone)]
+struct SyntaxError(&'static str);
+
+impl Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+impl std::error::Error for SyntaxError {}
+
+#[derive(Debug, Clone)]
+struct DuplicateKey(String);
+
+impl Display for DuplicateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "duplicate key `{}`", self.0)
+    }
+}
+
+impl std::error::Error for DuplicateKey {}
+
+#[derive(Debug, Clone)]
+struct Unterminated {
+    name: &'static str,
+    open: char,
+    close: char,
+    at: usize,
 }
 
+impl Display for Unterminated {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unterminated {}: expected `{}` to close the `{}` at byte {}",
+            self.name, self.close, self.open, self.at
+        )
+    }
+}
+
+impl std::error::Error for Unterminated {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn input_of<'i, I: JsonStream<'i>>(input: I) -> Input<I> {
+        new_input(input, ParseState::default())
+    }
+
     #[test]
     fn test_parse_null() -> PResult<(), ContextError> {
         let input = "null";
-        parse_null(&mut (&*input))?;
+        parse_null(&mut input_of(input))?;
 
         Ok(())
     }
@@ -147,11 +1262,11 @@ mod tests {
     #[test]
     fn test_parse_bool() -> PResult<(), ContextError> {
         let input = "true";
-        let result = parse_bool(&mut (&*input))?;
+        let result = parse_bool(&mut input_of(input))?;
         assert!(result);
 
         let input = "false";
-        let result = parse_bool(&mut (&*input))?;
+        let result = parse_bool(&mut input_of(input))?;
         assert!(!result);
 
         Ok(())
@@ -160,19 +1275,19 @@ mod tests {
     #[test]
     fn test_parse_num() -> PResult<(), ContextError> {
         let input = "123";
-        let result = parse_num(&mut (&*input))?;
+        let result = parse_num(&mut input_of(input))?;
         assert_eq!(result, Num::Int(123));
 
         let input = "-456";
-        let result = parse_num(&mut (&*input))?;
+        let result = parse_num(&mut input_of(input))?;
         assert_eq!(result, Num::Int(-456));
 
         let input = "123.456";
-        let result = parse_num(&mut (&*input))?;
+        let result = parse_num(&mut input_of(input))?;
         assert_eq!(result, Num::Float(123.456));
 
         let input = "-789.12";
-        let result = parse_num(&mut (&*input))?;
+        let result = parse_num(&mut input_of(input))?;
         assert_eq!(result, Num::Float(-789.12));
 
         Ok(())
@@ -187,10 +1302,42 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_parse_string_without_escapes_borrows() -> PResult<(), ContextError> {
+        let input = r#""hello world""#;
+        let result = parse_string(&mut (&*input))?;
+        assert!(matches!(result, Cow::Borrowed("hello world")));
+
+        let input: &[u8] = b"\"hello world\"";
+        let result = parse_string(&mut (&*input))?;
+        assert!(matches!(result, Cow::Borrowed("hello world")));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_string_with_escapes() -> PResult<(), ContextError> {
+        let input = r#""a\"b\\c\/d\n\u00e9\ud83d\ude00""#;
+        let result = parse_string(&mut (&*input))?;
+        assert!(matches!(result, Cow::Owned(_)));
+        assert_eq!(result, "a\"b\\c/d\né😀");
+
+        let input = r#""\x""#;
+        assert!(parse_string(&mut (&*input)).is_err());
+
+        // lone surrogates
+        let input = r#""\udc00""#;
+        assert!(parse_string(&mut (&*input)).is_err());
+        let input = r#""\ud800""#;
+        assert!(parse_string(&mut (&*input)).is_err());
+
+        Ok(())
+    }
+
     #[test]
     fn test_parse_array() -> PResult<(), ContextError> {
         let input = r#"[1,2,3]"#;
-        let result = parse_array(&mut (&*input))?;
+        let result = parse_array(&mut input_of(input))?;
         assert_eq!(
             result,
             vec![
@@ -201,7 +1348,7 @@ mod tests {
         );
 
         let input = r#"["a", "b", "c"]"#;
-        let result = parse_array(&mut (&*input))?;
+        let result = parse_array(&mut input_of(input))?;
         assert_eq!(
             result,
             vec![
@@ -217,23 +1364,23 @@ mod tests {
     #[test]
     fn test_parse_object() -> PResult<(), ContextError> {
         let input = r#"{"a":1,"b":2}"#;
-        let result = parse_object(&mut (&*input))?;
+        let result = parse_object(&mut input_of(input))?;
         assert_eq!(
             result,
             HashMap::from([
-                ("a".to_string(), JsonValue::Number(Num::Int(1))),
-                ("b".to_string(), JsonValue::Number(Num::Int(2)))
+                ("a".into(), JsonValue::Number(Num::Int(1))),
+                ("b".into(), JsonValue::Number(Num::Int(2)))
             ])
         );
 
         let input = r#"{"a":1, "b":[1, 2, 3]}"#;
-        let result = parse_object(&mut (&*input))?;
+        let result = parse_object(&mut input_of(input))?;
         assert_eq!(
             result,
             HashMap::from([
-                ("a".to_string(), JsonValue::Number(Num::Int(1))),
+                ("a".into(), JsonValue::Number(Num::Int(1))),
                 (
-                    "b".to_string(),
+                    "b".into(),
                     JsonValue::Array(vec![
                         JsonValue::Number(Num::Int(1)),
                         JsonValue::Number(Num::Int(2)),
@@ -245,4 +1392,758 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_parse_num_exponent() -> PResult<(), ContextError> {
+        for (input, expected) in [("1e2", 100.0), ("-2.5E-3", -0.0025), ("7e+1", 70.0)] {
+            let result = parse_num(&mut input_of(input))?;
+            assert_eq!(result, Num::Float(expected));
+        }
+
+        let input = "1e";
+        let mut input = input_of(input);
+        assert_eq!(parse_num(&mut input)?, Num::Int(1));
+        assert_eq!(input.input, "e");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_num_keeps_fraction_digits() -> PResult<(), ContextError> {
+        let input = "1.05";
+        let result = parse_num(&mut input_of(input))?;
+        assert_eq!(result, Num::Float(1.05));
+
+        Ok(())
+    }
+
+    // found by the `parse_json` fuzz target: this used to overflow the stack
+    #[test]
+    fn test_parse_deeply_nested_should_fail() {
+        let input = "[".repeat(100_000);
+        assert!(parse_json(&input).is_err());
+
+        let input = r#"{"a":"#.repeat(100_000);
+        assert!(parse_json(&input).is_err());
+    }
+
+    #[test]
+    fn test_parse_nested_within_limit() {
+        let input = format!("{}{}", "[".repeat(MAX_DEPTH), "]".repeat(MAX_DEPTH));
+        assert!(parse_json(&input).is_ok());
+    }
+
+    #[test]
+    fn test_parse_scalars_over_bytes() -> PResult<(), ContextError> {
+        let input: &[u8] = b"true";
+        assert!(parse_bool(&mut input_of(input))?);
+
+        let input: &[u8] = b"-789.12";
+        let result = parse_num(&mut input_of(input))?;
+        assert_eq!(result, Num::Float(-789.12));
+
+        let input: &[u8] = b"\"hello\"";
+        let result = parse_string(&mut (&*input))?;
+        assert_eq!(result, "hello");
+
+        let input: &[u8] = b"\"\xff\"";
+        assert!(parse_string(&mut (&*input)).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_value_over_str_and_bytes() -> PResult<(), ContextError> {
+        let input = r#"{"a":1, "b":[true, null, "c"]}"#;
+        let from_str = parse_value(&mut input_of(input))?;
+        let from_bytes = parse_value(&mut input_of(input.as_bytes()))?;
+        assert_eq!(from_str, from_bytes);
+        assert_eq!(
+            from_bytes,
+            JsonValue::Object(HashMap::from([
+                ("a".into(), JsonValue::Number(Num::Int(1))),
+                (
+                    "b".into(),
+                    JsonValue::Array(vec![
+                        JsonValue::Bool(true),
+                        JsonValue::Null,
+                        JsonValue::String("c".to_string())
+                    ])
+                )
+            ]))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_empty_object() -> PResult<(), ContextError> {
+        let input = "{ }";
+        let result = parse_object(&mut input_of(input))?;
+        assert!(result.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_display() {
+        let value = JsonValue::Array(vec![
+            JsonValue::Null,
+            JsonValue::Bool(true),
+            JsonValue::Number(Num::Int(-1)),
+            JsonValue::Number(Num::Float(1.0)),
+            JsonValue::Number(Num::Float(0.5)),
+            JsonValue::String("a\"b\\c\n\u{1}".to_string()),
+            JsonValue::Object(HashMap::from([("k".into(), JsonValue::Array(vec![]))])),
+        ]);
+        assert_eq!(
+            value.to_string(),
+            r#"[null,true,-1,1.0,0.5,"a\"b\\c\n\u0001",{"k":[]}]"#
+        );
+    }
+
+    #[test]
+    fn test_parse_json_scalar_root() -> Result<()> {
+        assert_eq!(parse_json("42")?, JsonValue::Number(Num::Int(42)));
+        assert_eq!(parse_json("-1.5")?, JsonValue::Number(Num::Float(-1.5)));
+        assert_eq!(
+            parse_json(r#""hello""#)?,
+            JsonValue::String("hello".to_string())
+        );
+        assert_eq!(parse_json("  true  ")?, JsonValue::Bool(true));
+        assert_eq!(parse_json("false")?, JsonValue::Bool(false));
+        assert_eq!(parse_json("\nnull\n")?, JsonValue::Null);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_json_trailing_garbage() {
+        assert!(parse_json("true x").is_err());
+        assert!(parse_json("1 2").is_err());
+        assert!(parse_json(r#"{"a":1}}"#).is_err());
+        assert!(parse_json("1 \n x").is_err());
+    }
+
+    #[test]
+    fn test_parse_json_skips_byte_order_mark() -> Result<()> {
+        assert_eq!(parse_json("\u{FEFF}{}")?, parse_json("{}")?);
+        assert_eq!(
+            parse_json("\u{FEFF} [1]\n")?,
+            parse_json_bytes(b"\xEF\xBB\xBF [1]\n")?
+        );
+        // only one, and only at the start
+        assert!(parse_json("\u{FEFF}\u{FEFF}{}").is_err());
+        assert!(parse_json(" \u{FEFF}{}").is_err());
+
+        let plain = parse_json_partial("[1, x").unwrap_err();
+        let err = parse_json_partial("\u{FEFF}[1, x").unwrap_err();
+        assert_eq!(err.consumed, '\u{FEFF}'.len_utf8() + plain.consumed);
+        assert_eq!(err.rest, plain.rest);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_json_trims_root_whitespace() -> Result<()> {
+        for input in ["  42", "42  ", "\n\t42\r\n", " \n 42 \n "] {
+            assert_eq!(
+                parse_json(input)?,
+                JsonValue::Number(Num::Int(42)),
+                "{:?}",
+                input
+            );
+        }
+        let object = parse_json(r#"{"a": 1}"#)?;
+        for input in ["\n    {\"a\": 1}\n", "\t{\"a\": 1}  \r\n\r\n"] {
+            assert_eq!(parse_json(input)?, object, "{:?}", input);
+        }
+        assert!(parse_json("").is_err());
+        assert!(parse_json(" \n ").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_json_string_into_reuses_buffer() -> Result<()> {
+        let mut buf = String::with_capacity(64);
+        let capacity = buf.capacity();
+
+        parse_json_string_into(r#""a\"b\u00e9\n""#, &mut buf)?;
+        assert_eq!(buf, "a\"b\u{e9}\n");
+
+        parse_json_string_into(r#" "plain" "#, &mut buf)?;
+        assert_eq!(buf, "plain");
+        assert_eq!(buf.capacity(), capacity);
+
+        parse_json_string_into("\u{feff}\"bom\"", &mut buf)?;
+        assert_eq!(buf, "bom");
+
+        assert!(parse_json_string_into("42", &mut buf).is_err());
+        assert!(parse_json_string_into(r#""a" "b""#, &mut buf).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_peek_kind() {
+        assert_eq!(peek_kind(r#"{"a": [1, 2]}"#), Some(JsonKind::Object));
+        assert_eq!(peek_kind(" \n[1, 2]"), Some(JsonKind::Array));
+        assert_eq!(peek_kind("-12.5"), Some(JsonKind::Number));
+        assert_eq!(peek_kind("\u{FEFF}\"hello\""), Some(JsonKind::String));
+        assert_eq!(peek_kind("true"), Some(JsonKind::Bool));
+        assert_eq!(peek_kind("null"), Some(JsonKind::Null));
+        assert_eq!(peek_kind("   "), None);
+        assert_eq!(peek_kind("<xml/>"), None);
+    }
+
+    #[test]
+    fn test_parse_unterminated_array() {
+        let err = parse_json("[1, 2, 3").unwrap_err().to_string();
+        assert!(
+            err.contains("unterminated array: expected `]` to close the `[` at byte 0"),
+            "{}",
+            err
+        );
+
+        let err = parse_json(r#"{"a": [1, [2, 3], "#).unwrap_err().to_string();
+        assert!(err.contains("the `[` at byte 6"), "{}", err);
+    }
+
+    #[test]
+    fn test_parse_unterminated_object() {
+        let err = parse_json(r#"[{"a": 1, "b": 2"#).unwrap_err().to_string();
+        assert!(
+            err.contains("unterminated object: expected `}` to close the `{` at byte 1"),
+            "{}",
+            err
+        );
+
+        // not truncated, just wrong
+        let err = parse_json(r#"{"a": 1 "b": 2}"#).unwrap_err().to_string();
+        assert!(!err.contains("unterminated"), "{}", err);
+    }
+
+    #[test]
+    fn test_parse_json_partial_reports_consumed_prefix() {
+        let input = r#"{"a": 1, "b": [2, 3]"#;
+        let err = parse_json_partial(input).unwrap_err();
+        assert_eq!(err.consumed, input.len());
+        assert_eq!(err.rest, "");
+        assert!(err.to_string().contains("unterminated object"), "{}", err);
+
+        let input = r#"{"a": 1}} "#;
+        let err = parse_json_partial(input).unwrap_err();
+        assert_eq!(err.consumed, 8);
+        assert_eq!(err.rest, "} ");
+
+        assert!(parse_json_partial("[1, 2]").is_ok());
+    }
+
+    #[test]
+    fn test_parse_object_colon_errors() {
+        let err = parse_json(r#"{"a" "b": 1}"#).unwrap_err().to_string();
+        assert!(err.contains("expected `:` after key"), "{}", err);
+
+        let err = parse_json(r#"{"a":: 1}"#).unwrap_err().to_string();
+        assert!(err.contains("unexpected `:`"), "{}", err);
+
+        let err = parse_json(r#"{"a": : 1}"#).unwrap_err().to_string();
+        assert!(err.contains("unexpected `:`"), "{}", err);
+    }
+
+    #[test]
+    fn test_num_as_i64_lossless() {
+        assert_eq!(Num::Int(30).as_i64_lossless(), Some(30));
+        assert_eq!(Num::Float(30.0).as_i64_lossless(), Some(30));
+        assert_eq!(Num::Float(-0.0).as_i64_lossless(), Some(0));
+        assert_eq!(Num::Float(30.5).as_i64_lossless(), None);
+        assert_eq!(Num::Float(9.3e18).as_i64_lossless(), None);
+        assert_eq!(
+            Num::Float(i64::MIN as f64).as_i64_lossless(),
+            Some(i64::MIN)
+        );
+        assert_eq!(Num::Float(f64::NAN).as_i64_lossless(), None);
+        assert_eq!(Num::Float(f64::INFINITY).as_i64_lossless(), None);
+    }
+
+    #[test]
+    fn test_display_float_formatting() {
+        assert_eq!(Num::Float(1.0).to_string(), "1.0");
+        assert_eq!(Num::Float(0.1).to_string(), "0.1");
+        assert_eq!(Num::Float(0.1 + 0.2).to_string(), "0.30000000000000004");
+        assert_eq!(Num::Float(-1e20).to_string(), "-100000000000000000000.0");
+
+        assert_eq!(format!("{:.3}", Num::Float(0.1 + 0.2)), "0.300");
+        assert_eq!(format!("{:.0}", Num::Float(2.5)), "2.0");
+        assert_eq!(format!("{:.2}", Num::Int(7)), "7");
+
+        let value = JsonValue::Array(vec![
+            JsonValue::Number(Num::Float(1.23456)),
+            JsonValue::String("1.23456".to_string()),
+            JsonValue::Number(Num::Int(1)),
+        ]);
+        assert_eq!(format!("{:.2}", value), r#"[1.23,"1.23456",1]"#);
+    }
+
+    #[test]
+    fn test_parse_json_strict_root() -> Result<()> {
+        assert_eq!(
+            parse_json_strict_root(r#" {"a": 1} "#)?,
+            parse_json(r#"{"a": 1}"#)?
+        );
+        assert!(parse_json_strict_root("[]").is_ok());
+
+        let err = parse_json_strict_root("42").unwrap_err().to_string();
+        assert!(err.contains("root must be an object or array"), "{}", err);
+        assert!(parse_json_strict_root(r#""text""#).is_err());
+        assert!(parse_json_strict_root("[1").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_array_prefix() -> Result<()> {
+        let input = r#" [{"id": 1}, {"id": 2}, {"id": 3}, {"id": "#;
+        assert_eq!(
+            parse_array_prefix(input, 2)?,
+            vec![parse_json(r#"{"id": 1}"#)?, parse_json(r#"{"id": 2}"#)?]
+        );
+        assert_eq!(parse_array_prefix(input, 0)?, vec![]);
+        assert_eq!(
+            parse_array_prefix("[1, 2]", 5)?,
+            parse_json("[1, 2]")?.into_iter().collect::<Vec<_>>()
+        );
+        assert!(parse_array_prefix(input, 4).is_err());
+        assert!(parse_array_prefix("[1, x]", 2).is_err());
+        assert!(parse_array_prefix(r#"{"a": 1}"#, 1).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_json_compact() -> Result<()> {
+        let input = r#"{"a":[1,2.5,"x y"],"b":{"c":null,"d":true}}"#;
+        assert_eq!(parse_json_compact(input)?, parse_json(input)?);
+
+        assert!(parse_json_compact(r#"{"a": 1}"#).is_err());
+        assert!(parse_json_compact("[1,\n2]").is_err());
+        assert!(parse_json_compact(" 1").is_err());
+        assert!(parse_json_compact("1 ").is_err());
+        assert_eq!(parse_json_compact("\u{feff}[1]")?, parse_json("[1]")?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_options_strict_and_lenient() -> Result<()> {
+        let extensions = [
+            "[1]\x0b",
+            "[1, // one\n 2]",
+            "/* list */ [1, 2]",
+            "[+1]",
+            "[1_000]",
+            "[.5]",
+            "[TRUE, Null]",
+            "{a: 1}",
+            "[1, 2,]",
+            r#"{"a": 1,}"#,
+            "[NaN, -Infinity]",
+            "[1e999, -1e999]",
+        ];
+        for input in extensions {
+            assert!(
+                parse_json_with(input, ParseOptions::strict()).is_err(),
+                "{:?}",
+                input
+            );
+            assert!(
+                parse_json_with(input, ParseOptions::lenient()).is_ok(),
+                "{:?}",
+                input
+            );
+        }
+        // not supported in either mode
+        for input in ["'a'", "[nan]", "[+Infinity]", "[1,,2]"] {
+            assert!(parse_json_with(input, ParseOptions::lenient()).is_err());
+        }
+        let big = format!("1{}", "0".repeat(400));
+        assert!(parse_json(&big).is_err());
+        let (value, warnings) = parse_json_with_warnings(&big, ParseOptions::lenient())?;
+        assert_eq!(value, JsonValue::Number(Num::Float(f64::INFINITY)));
+        assert_eq!(warnings[0].kind, WarningKind::NonFinite);
+        let input = r#"{"a": [1, 2.5e3, "x\u00e9"], "b": null}"#;
+        assert_eq!(
+            parse_json_with(input, ParseOptions::strict())?,
+            parse_json_with(input, ParseOptions::lenient())?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_json_lenient_whitespace() -> Result<()> {
+        let input = "[1,\x0C2]\x0B";
+        let lenient = ParseOptions {
+            lenient: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            parse_json_with(input, lenient)?,
+            JsonValue::Array(vec![
+                JsonValue::Number(Num::Int(1)),
+                JsonValue::Number(Num::Int(2))
+            ])
+        );
+        assert!(parse_json(input).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_json_lenient_comments() -> Result<()> {
+        let input = "// list\n[1, /* two */ 2] // done";
+        let lenient = ParseOptions {
+            lenient: true,
+            ..Default::default()
+        };
+        let (value, warnings) = parse_json_with_warnings(input, lenient.clone())?;
+        assert_eq!(value, parse_json("[1, 2]")?);
+        assert_eq!(
+            warnings.iter().map(|w| w.offset).collect::<Vec<_>>(),
+            vec![0, 12, 25]
+        );
+        assert!(parse_json(input).is_err());
+        assert!(parse_json_with("[1 /* two", lenient).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_skip_insignificant() -> Result<()> {
+        let mut input = " \n\t/* x */1";
+        skip_insignificant(&mut input).unwrap();
+        assert_eq!(input, "/* x */1");
+
+        let lenient = ParseOptions {
+            lenient: true,
+            ..Default::default()
+        };
+        skip_insignificant_with(lenient)
+            .parse_next(&mut input)
+            .unwrap();
+        assert_eq!(input, "1");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_json_lenient_keyword_case() -> Result<()> {
+        let lenient = ParseOptions {
+            lenient: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            parse_json_with("[TRUE, False, Null]", lenient.clone())?,
+            JsonValue::Array(vec![
+                JsonValue::Bool(true),
+                JsonValue::Bool(false),
+                JsonValue::Null
+            ])
+        );
+        assert!(parse_json_with("Nul", lenient).is_err());
+        assert!(parse_json("TRUE").is_err());
+        assert!(parse_json("Null").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_json_lenient_unquoted_keys() -> Result<()> {
+        let lenient = ParseOptions {
+            lenient: true,
+            ..Default::default()
+        };
+        let value = parse_json_with(
+            r#"{name: "John", $id_2: 1, "quoted": true}"#,
+            lenient.clone(),
+        )?;
+        assert_eq!(value.get("name"), Some(&JsonValue::String("John".into())));
+        assert_eq!(value.get("$id_2"), Some(&JsonValue::Number(Num::Int(1))));
+        assert_eq!(value.get("quoted"), Some(&JsonValue::Bool(true)));
+
+        assert!(parse_json_with("{2name: 1}", lenient).is_err());
+        assert!(parse_json(r#"{name: "John"}"#).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_json_lenient_underscores() -> Result<()> {
+        let lenient = ParseOptions {
+            lenient: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            parse_json_with("1_000", lenient.clone())?,
+            JsonValue::Number(Num::Int(1000))
+        );
+        assert_eq!(
+            parse_json_with("-1_000_000.000_5", lenient.clone())?,
+            JsonValue::Number(Num::Float(-1_000_000.000_5))
+        );
+        for input in ["_1000", "1000_", "1__000", "1_.5", "1._5"] {
+            assert!(
+                parse_json_with(input, lenient.clone()).is_err(),
+                "{}",
+                input
+            );
+        }
+        assert!(parse_json("1_000").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_json_lenient_trailing_comma_warns() -> Result<()> {
+        let lenient = ParseOptions {
+            lenient: true,
+            ..Default::default()
+        };
+        let (value, warnings) = parse_json_with_warnings("[1, 2, ]", lenient.clone())?;
+        assert_eq!(value, parse_json("[1, 2]")?);
+        assert_eq!(
+            warnings,
+            [Warning {
+                offset: 5,
+                kind: WarningKind::TrailingComma
+            }]
+        );
+        assert_eq!(warnings[0].to_string(), "trailing comma at byte 5");
+
+        let (_, warnings) = parse_json_with_warnings(r#"{a: +1,}"#, lenient.clone())?;
+        let kinds: Vec<_> = warnings.iter().map(|w| w.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                WarningKind::UnquotedKey,
+                WarningKind::PlusSign,
+                WarningKind::TrailingComma
+            ]
+        );
+
+        assert!(parse_json_with("[,]", lenient.clone()).is_err());
+        assert!(parse_json_with("[1,,]", lenient).is_err());
+        assert!(parse_json("[1, 2, ]").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_json_lenient_missing_integer_part() -> Result<()> {
+        let lenient = ParseOptions {
+            lenient: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            parse_json_with(".5", lenient.clone())?,
+            JsonValue::Number(Num::Float(0.5))
+        );
+        assert_eq!(
+            parse_json_with("[-.25]", lenient.clone())?,
+            JsonValue::Array(vec![JsonValue::Number(Num::Float(-0.25))])
+        );
+        let (_, warnings) = parse_json_with_warnings("[.5]", lenient.clone())?;
+        assert_eq!(
+            warnings,
+            [Warning {
+                offset: 1,
+                kind: WarningKind::MissingIntegerPart
+            }]
+        );
+        assert!(parse_json_with(".", lenient.clone()).is_err());
+        assert!(parse_json_with("5.", lenient).is_err());
+        assert!(parse_json(".5").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_json_lenient_plus_sign() -> Result<()> {
+        let lenient = ParseOptions {
+            lenient: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            parse_json_with("+5", lenient.clone())?,
+            JsonValue::Number(Num::Int(5))
+        );
+        assert_eq!(
+            parse_json_with("+1.5", lenient.clone())?,
+            JsonValue::Number(Num::Float(1.5))
+        );
+        assert!(parse_json_with("+-5", lenient).is_err());
+        assert!(parse_json("+5").is_err());
+        assert!(parse_json("+1.5").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_json_reports_expected_values() {
+        let err = parse_json("@").unwrap_err().to_string();
+        assert!(
+            err.contains("expected one of: null, true/false, number, string, array, object"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_parse_json_number_mode() -> Result<()> {
+        assert_eq!(parse_json("5")?, JsonValue::Number(Num::Int(5)));
+
+        let always_float = ParseOptions {
+            number_mode: NumberMode::AlwaysFloat,
+            ..Default::default()
+        };
+        assert_eq!(
+            parse_json_with("5", always_float.clone())?,
+            JsonValue::Number(Num::Float(5.0))
+        );
+        assert_eq!(
+            parse_json_with("[-5, 0.5]", always_float.clone())?,
+            JsonValue::Array(vec![
+                JsonValue::Number(Num::Float(-5.0)),
+                JsonValue::Number(Num::Float(0.5))
+            ])
+        );
+
+        let prefer_int = ParseOptions {
+            number_mode: NumberMode::PreferInt,
+            ..Default::default()
+        };
+        assert_eq!(
+            parse_json_with("1e2", prefer_int.clone())?,
+            JsonValue::Number(Num::Int(100))
+        );
+        assert_eq!(
+            parse_json_with(
+                "[-1.50e1, 2.0, 1200e-2, 0e-9, 9007199254740993e0]",
+                prefer_int.clone()
+            )?,
+            parse_json("[-15, 2, 12, 0, 9007199254740993]")?
+        );
+        assert_eq!(
+            parse_json_with("[1.5, 1e-1, 1e19, -1e19]", prefer_int.clone())?,
+            parse_json("[1.5, 0.1, 1e19, -1e19]")?
+        );
+
+        let big = "[99999999999999999999, -9223372036854775809]";
+        let expected = JsonValue::Array(vec![
+            JsonValue::Number(Num::Float(1e20)),
+            JsonValue::Number(Num::Float(-9223372036854775809.0)),
+        ]);
+        assert_eq!(parse_json(big)?, expected);
+        for options in [always_float, prefer_int] {
+            assert_eq!(parse_json_with(big, options)?, expected);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_json_key_policy() -> Result<()> {
+        let input = r#"{"a": 1, "b": 0, "a": 2}"#;
+        let with = |key_policy| ParseOptions {
+            key_policy,
+            ..Default::default()
+        };
+        assert_eq!(parse_json(input)?, parse_json(r#"{"a": 2, "b": 0}"#)?);
+        assert_eq!(
+            parse_json_with(input, with(KeyPolicy::KeepFirst))?,
+            parse_json(r#"{"a": 1, "b": 0}"#)?
+        );
+        let err = parse_json_with(input, with(KeyPolicy::Error)).unwrap_err();
+        assert!(err.to_string().contains("duplicate key `a`"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_json_merge_keys_to_array() -> Result<()> {
+        let merge = ParseOptions {
+            key_policy: KeyPolicy::MergeToArray,
+            ..Default::default()
+        };
+        assert_eq!(
+            parse_json_with(r#"{"a": 1, "a": 2}"#, merge.clone())?,
+            parse_json(r#"{"a": [1, 2]}"#)?
+        );
+        assert_eq!(
+            parse_json_with(r#"{"a": [1], "b": 0, "a": 2, "a": {"c": 3}}"#, merge)?,
+            parse_json(r#"{"a": [[1], 2, {"c": 3}], "b": 0}"#)?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_json_bytes_from_file() -> Result<()> {
+        let text =
+            "{\"name\": \"Żółw\", \"tags\": [\"a\", \"b\"], \"score\": 9.5e1, \"ok\": true}\n";
+        let path =
+            std::env::temp_dir().join(format!("parse_json_bytes_{}.json", std::process::id()));
+        std::fs::write(&path, text)?;
+        let bytes = std::fs::read(&path);
+        std::fs::remove_file(&path)?;
+
+        assert_eq!(parse_json_bytes(&bytes?)?, parse_json(text)?);
+        assert!(parse_json_bytes(b"[1, 2").is_err());
+        assert!(parse_json_bytes(b"\"\xff\"").is_err());
+        assert!(parse_json_bytes(b"true x").is_err());
+
+        let input = "{a: [1, 2,], // two\n}";
+        let lenient = parse_json_bytes_with(input.as_bytes(), ParseOptions::lenient())?;
+        assert_eq!(lenient, parse_json_with(input, ParseOptions::lenient())?);
+        assert!(parse_json_bytes(input.as_bytes()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_json_max_nodes() -> Result<()> {
+        let options = ParseOptions {
+            max_nodes: Some(4),
+            ..Default::default()
+        };
+        let value = parse_json_with(r#"[1, {"a": null}]"#, options.clone())?;
+        assert_eq!(value.len(), 2);
+
+        let input = format!("[{}]", vec!["{}"; 1000].join(","));
+        let err = parse_json_with(&input, options.clone()).unwrap_err();
+        assert!(err.to_string().contains("node count"), "{}", err);
+        assert!(parse_json_with("[1, 2, 3, 4]", options).is_err());
+        assert!(parse_json(&input).is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_json_intern_keys() -> Result<()> {
+        let input = r#"[{"id": 1, "name": "a"}, {"id": 2, "name": "b"}]"#;
+        let id_key = |value: &JsonValue, i: usize| {
+            let record = value.get_index(i).unwrap().as_object().unwrap();
+            Arc::clone(record.get_key_value("id").unwrap().0)
+        };
+
+        let intern = ParseOptions {
+            intern_keys: true,
+            ..Default::default()
+        };
+        let interned = parse_json_with(input, intern)?;
+        assert!(Arc::ptr_eq(&id_key(&interned, 0), &id_key(&interned, 1)));
+
+        let value = parse_json(input)?;
+        assert!(!Arc::ptr_eq(&id_key(&value, 0), &id_key(&value, 1)));
+        assert_eq!(interned, value);
+
+        Ok(())
+    }
 }
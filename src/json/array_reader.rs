@@ -0,0 +1,181 @@
+use super::{parse_json, JsonValue};
+use anyhow::{anyhow, Result};
+use std::io::BufRead;
+
+/// Iterates over the elements of a top-level JSON array read from `reader`,
+/// holding only the element being read in memory.
+///
+/// Each element is cut out of the stream by tracking brackets and strings and
+/// then parsed with [`parse_json`]. Iteration stops after the closing `]` or
+/// the first error; anything after the array is not read.
+#[derive(Debug)]
+pub struct JsonArrayReader<R> {
+    reader: R,
+    buf: Vec<u8>,
+    scan: Scan,
+    state: State,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum State {
+    Start,
+    Elements { count: usize },
+    Done,
+}
+
+// where the search for the end of the current element left off
+#[derive(Debug, Default)]
+struct Scan {
+    pos: usize,
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+}
+
+impl<R: BufRead> JsonArrayReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buf: Vec::new(),
+            scan: Scan::default(),
+            state: State::Start,
+        }
+    }
+
+    // appends the next chunk of the reader to `buf`, false at the end of input
+    fn fill(&mut self) -> Result<bool> {
+        let chunk = self.reader.fill_buf()?;
+        let len = chunk.len();
+        self.buf.extend_from_slice(chunk);
+        self.reader.consume(len);
+        Ok(len > 0)
+    }
+
+    fn open(&mut self) -> Result<()> {
+        loop {
+            match self.buf.iter().position(|b| !b.is_ascii_whitespace()) {
+                Some(i) if self.buf[i] == b'[' => {
+                    self.buf.drain(..=i);
+                    return Ok(());
+                }
+                Some(i) => return Err(anyhow!("expected `[`, found {:?}", self.buf[i] as char)),
+                None if !self.fill()? => return Err(anyhow!("expected `[`, found end of input")),
+                None => {}
+            }
+        }
+    }
+
+    // the position of the `,` or `]` that ends the current element
+    fn find_end(&mut self) -> Result<usize> {
+        loop {
+            while let Some(&b) = self.buf.get(self.scan.pos) {
+                let scan = &mut self.scan;
+                if scan.in_string {
+                    match b {
+                        _ if scan.escaped => scan.escaped = false,
+                        b'\\' => scan.escaped = true,
+                        b'"' => scan.in_string = false,
+                        _ => {}
+                    }
+                } else {
+                    match b {
+                        b'"' => scan.in_string = true,
+                        b'[' | b'{' => scan.depth += 1,
+                        b',' | b']' if scan.depth == 0 => return Ok(scan.pos),
+                        b']' | b'}' => scan.depth = scan.depth.saturating_sub(1),
+                        _ => {}
+                    }
+                }
+                scan.pos += 1;
+            }
+            if !self.fill()? {
+                return Err(anyhow!("unterminated array: expected `]`"));
+            }
+        }
+    }
+
+    fn next_element(&mut self) -> Result<Option<JsonValue>> {
+        if self.state == State::Start {
+            self.open()?;
+            self.state = State::Elements { count: 0 };
+        }
+        let State::Elements { count } = self.state else {
+            return Ok(None);
+        };
+        let end = self.find_end()?;
+        let last = self.buf[end] == b']';
+        let element = std::str::from_utf8(&self.buf[..end])?;
+        let ret = if last && count == 0 && element.trim().is_empty() {
+            None
+        } else {
+            let value = parse_json(element).map_err(|e| anyhow!("element {}: {}", count, e))?;
+            Some(value)
+        };
+        self.buf.drain(..=end);
+        self.scan = Scan::default();
+        self.state = if last {
+            State::Done
+        } else {
+            State::Elements { count: count + 1 }
+        };
+        Ok(ret)
+    }
+}
+
+impl<R: BufRead> Iterator for JsonArrayReader<R> {
+    type Item = Result<JsonValue>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let ret = self.next_element();
+        if ret.is_err() {
+            self.state = State::Done;
+        }
+        ret.transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::Num;
+    use std::io::{BufReader, Cursor};
+
+    fn tiny_reader(input: &str) -> JsonArrayReader<impl BufRead + '_> {
+        JsonArrayReader::new(BufReader::with_capacity(2, Cursor::new(input)))
+    }
+
+    #[test]
+    fn test_array_reader_streams_elements() -> Result<()> {
+        let values = tiny_reader("[1,2,3]").collect::<Result<Vec<_>>>()?;
+        assert_eq!(
+            values,
+            [
+                JsonValue::Number(Num::Int(1)),
+                JsonValue::Number(Num::Int(2)),
+                JsonValue::Number(Num::Int(3))
+            ]
+        );
+
+        let input = r#" [ {"a": [1, 2]}, "x,]\"y" , [] ] "#;
+        let values = tiny_reader(input).collect::<Result<Vec<_>>>()?;
+        assert_eq!(values, parse_json(input)?.as_array().unwrap().clone());
+
+        assert_eq!(tiny_reader(" [ ] ").count(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_array_reader_stops_on_error() {
+        let mut reader = tiny_reader("[1, , 3]");
+        assert!(reader.next().unwrap().is_ok());
+        assert!(reader.next().unwrap().is_err());
+        assert!(reader.next().is_none());
+
+        let mut reader = tiny_reader("[1, 2");
+        assert!(reader.next().unwrap().is_ok());
+        let err = reader.next().unwrap().unwrap_err().to_string();
+        assert!(err.contains("unterminated array"), "{}", err);
+
+        assert!(tiny_reader("{}").next().unwrap().is_err());
+    }
+}
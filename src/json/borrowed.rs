@@ -0,0 +1,143 @@
+use super::{
+    array_of, count_node, object_of, parse_bool, parse_document, parse_key, parse_null, parse_num,
+    parse_string, Input, JsonStream, JsonValue, Num, ParseState, EXPECTED_VALUE,
+};
+use anyhow::{anyhow, Result};
+use std::{borrow::Cow, collections::HashMap, sync::Arc};
+use winnow::{combinator::alt, PResult, Parser};
+
+/// A JSON value whose strings borrow from the input wherever no unescaping
+/// was needed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonValueRef<'a> {
+    Null,
+    Bool(bool),
+    Number(Num),
+    String(Cow<'a, str>),
+    Array(Vec<JsonValueRef<'a>>),
+    Object(HashMap<Cow<'a, str>, JsonValueRef<'a>>),
+}
+
+/// Parses a complete JSON document like [`parse_json`](super::parse_json)
+/// without copying its strings.
+pub fn parse_json_ref(input: &str) -> Result<JsonValueRef<'_>> {
+    let (value, _) = parse_document(input, ParseState::default(), parse_value_ref)
+        .map_err(|e| anyhow!(e.message))?;
+    Ok(value)
+}
+
+fn parse_value_ref<'i, I: JsonStream<'i>>(input: &mut Input<I>) -> PResult<JsonValueRef<'i>> {
+    let value = alt((
+        parse_null.value(JsonValueRef::Null),
+        parse_bool.map(JsonValueRef::Bool),
+        parse_num.map(JsonValueRef::Number),
+        parse_string.map(JsonValueRef::String),
+        array_of(parse_value_ref).map(JsonValueRef::Array),
+        object_of(parse_key, parse_value_ref).map(JsonValueRef::Object),
+    ))
+    .context(EXPECTED_VALUE)
+    .parse_next(input)?;
+    count_node(input)?;
+    Ok(value)
+}
+
+impl JsonValueRef<'_> {
+    /// Deep-copies the value into an owned [`JsonValue`].
+    pub fn to_json_value(&self) -> JsonValue {
+        match self {
+            JsonValueRef::Null => JsonValue::Null,
+            JsonValueRef::Bool(b) => JsonValue::Bool(*b),
+            JsonValueRef::Number(n) => JsonValue::Number(n.clone()),
+            JsonValueRef::String(s) => JsonValue::String(s.to_string()),
+            JsonValueRef::Array(values) => {
+                JsonValue::Array(values.iter().map(JsonValueRef::to_json_value).collect())
+            }
+            JsonValueRef::Object(map) => JsonValue::Object(
+                map.iter()
+                    .map(|(k, v)| (Arc::from(k.as_ref()), v.to_json_value()))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Converts the value into an owned [`JsonValue`], reusing the strings that
+    /// are already owned.
+    pub fn into_owned(self) -> JsonValue {
+        match self {
+            JsonValueRef::Null => JsonValue::Null,
+            JsonValueRef::Bool(b) => JsonValue::Bool(b),
+            JsonValueRef::Number(n) => JsonValue::Number(n),
+            JsonValueRef::String(s) => JsonValue::String(s.into_owned()),
+            JsonValueRef::Array(values) => {
+                JsonValue::Array(values.into_iter().map(JsonValueRef::into_owned).collect())
+            }
+            JsonValueRef::Object(map) => JsonValue::Object(
+                map.into_iter()
+                    .map(|(k, v)| (Arc::from(k), v.into_owned()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl JsonValue {
+    /// Borrows the value as a [`JsonValueRef`] without copying any string.
+    pub fn as_value_ref(&self) -> JsonValueRef<'_> {
+        match self {
+            JsonValue::Null => JsonValueRef::Null,
+            JsonValue::Bool(b) => JsonValueRef::Bool(*b),
+            JsonValue::Number(n) => JsonValueRef::Number(n.clone()),
+            JsonValue::String(s) => JsonValueRef::String(Cow::Borrowed(s)),
+            JsonValue::Array(values) => {
+                JsonValueRef::Array(values.iter().map(JsonValue::as_value_ref).collect())
+            }
+            JsonValue::Object(map) => JsonValueRef::Object(
+                map.iter()
+                    .map(|(k, v)| (Cow::Borrowed(k.as_ref()), v.as_value_ref()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::parse_json;
+
+    #[test]
+    fn test_parse_json_ref_borrows_strings() -> Result<()> {
+        let input = r#"{"plain": "abc", "escaped": "a\nb"}"#;
+        let value = parse_json_ref(input)?;
+        let JsonValueRef::Object(map) = &value else {
+            panic!("expected an object, got {:?}", value);
+        };
+        assert!(matches!(
+            map.get("plain"),
+            Some(JsonValueRef::String(Cow::Borrowed("abc")))
+        ));
+        assert!(matches!(
+            map.get("escaped"),
+            Some(JsonValueRef::String(Cow::Owned(_)))
+        ));
+
+        let value = parse_json_ref("\u{feff} [\"a\"]")?;
+        assert_eq!(value.to_json_value(), parse_json(r#"["a"]"#)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_owned_is_independent_of_input() -> Result<()> {
+        let input = String::from(r#"{"a": [1, "x", {"b": null}], "c": true}"#);
+        let owned = {
+            let value = parse_json_ref(&input)?;
+            value.to_json_value()
+        };
+        drop(input);
+
+        let expected = parse_json(r#"{"a": [1, "x", {"b": null}], "c": true}"#)?;
+        assert_eq!(owned, expected);
+        assert_eq!(owned.as_value_ref().into_owned(), expected);
+        Ok(())
+    }
+}
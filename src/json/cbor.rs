@@ -0,0 +1,260 @@
+use super::{JsonValue, Num, MAX_DEPTH};
+use anyhow::{anyhow, bail, Result};
+use std::collections::HashMap;
+
+// CBOR (RFC 8949) major types
+const UNSIGNED: u8 = 0;
+const NEGATIVE: u8 = 1;
+const TEXT: u8 = 3;
+const ARRAY: u8 = 4;
+const MAP: u8 = 5;
+const SIMPLE: u8 = 7;
+
+const FALSE: u8 = 0xf4;
+const TRUE: u8 = 0xf5;
+const NULL: u8 = 0xf6;
+const FLOAT32: u8 = 0xfa;
+const FLOAT64: u8 = 0xfb;
+
+impl JsonValue {
+    /// Encodes the value as CBOR: integers, floats, text strings, arrays and
+    /// maps with text keys map onto the matching CBOR types.
+    pub fn to_cbor(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        encode(self, &mut out);
+        out
+    }
+
+    /// Decodes CBOR produced by [`to_cbor`](Self::to_cbor). Definite-length
+    /// items of the types it writes are accepted, plus single-precision floats;
+    /// anything else, such as byte strings or tags, is an error.
+    pub fn from_cbor(bytes: &[u8]) -> Result<JsonValue> {
+        let mut decoder = Decoder { bytes, pos: 0 };
+        let value = decoder.value(0)?;
+        if decoder.pos != bytes.len() {
+            bail!("trailing bytes after CBOR item at offset {}", decoder.pos);
+        }
+        Ok(value)
+    }
+}
+
+fn encode(value: &JsonValue, out: &mut Vec<u8>) {
+    match value {
+        JsonValue::Null => out.push(NULL),
+        JsonValue::Bool(false) => out.push(FALSE),
+        JsonValue::Bool(true) => out.push(TRUE),
+        JsonValue::Number(Num::Int(n)) if *n >= 0 => write_head(out, UNSIGNED, *n as u64),
+        // -1 - n without overflow for i64::MIN
+        JsonValue::Number(Num::Int(n)) => write_head(out, NEGATIVE, !(*n as u64)),
+        JsonValue::Number(Num::Float(n)) => {
+            out.push(FLOAT64);
+            out.extend_from_slice(&n.to_be_bytes());
+        }
+        JsonValue::String(s) => write_text(out, s),
+        JsonValue::Array(values) => {
+            write_head(out, ARRAY, values.len() as u64);
+            values.iter().for_each(|v| encode(v, out));
+        }
+        JsonValue::Object(map) => {
+            write_head(out, MAP, map.len() as u64);
+            for (k, v) in map {
+                write_text(out, k);
+                encode(v, out);
+            }
+        }
+    }
+}
+
+fn write_text(out: &mut Vec<u8>, s: &str) {
+    write_head(out, TEXT, s.len() as u64);
+    out.extend_from_slice(s.as_bytes());
+}
+
+// the initial byte and the argument in the shortest form
+fn write_head(out: &mut Vec<u8>, major: u8, arg: u64) {
+    let major = major << 5;
+    match arg {
+        0..=23 => out.push(major | arg as u8),
+        24..=0xff => out.extend_from_slice(&[major | 24, arg as u8]),
+        0x100..=0xffff => {
+            out.push(major | 25);
+            out.extend_from_slice(&(arg as u16).to_be_bytes());
+        }
+        0x1_0000..=0xffff_ffff => {
+            out.push(major | 26);
+            out.extend_from_slice(&(arg as u32).to_be_bytes());
+        }
+        _ => {
+            out.push(major | 27);
+            out.extend_from_slice(&arg.to_be_bytes());
+        }
+    }
+}
+
+struct Decoder<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Decoder<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8]> {
+        let end = self
+            .pos
+            .checked_add(n)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or_else(|| anyhow!("unexpected end of CBOR input at offset {}", self.pos))?;
+        let ret = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(ret)
+    }
+
+    fn take_array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        Ok(self.take(N)?.try_into().expect("took N bytes"))
+    }
+
+    fn argument(&mut self, info: u8) -> Result<u64> {
+        Ok(match info {
+            0..=23 => info as u64,
+            24 => self.take_array::<1>()?[0] as u64,
+            25 => u16::from_be_bytes(self.take_array()?) as u64,
+            26 => u32::from_be_bytes(self.take_array()?) as u64,
+            27 => u64::from_be_bytes(self.take_array()?),
+            _ => bail!(
+                "unsupported CBOR argument {} at offset {}",
+                info,
+                self.pos - 1
+            ),
+        })
+    }
+
+    fn len(&mut self, info: u8) -> Result<usize> {
+        let len = self.argument(info)?;
+        // every item takes at least one byte, which bounds any honest length
+        if len > (self.bytes.len() - self.pos) as u64 {
+            bail!("CBOR length {} exceeds the input", len);
+        }
+        Ok(len as usize)
+    }
+
+    fn text(&mut self, info: u8) -> Result<String> {
+        let len = self.len(info)?;
+        let start = self.pos;
+        let s = std::str::from_utf8(self.take(len)?)
+            .map_err(|e| anyhow!("invalid UTF-8 in CBOR text at offset {}: {}", start, e))?;
+        Ok(s.to_string())
+    }
+
+    fn value(&mut self, depth: usize) -> Result<JsonValue> {
+        if depth > MAX_DEPTH {
+            bail!("CBOR nested deeper than {}", MAX_DEPTH);
+        }
+        let start = self.pos;
+        let initial = self.take_array::<1>()?[0];
+        let (major, info) = (initial >> 5, initial & 0x1f);
+        let value = match (major, initial) {
+            (UNSIGNED, _) => {
+                let n = self.argument(info)?;
+                let n = i64::try_from(n).map_err(|_| anyhow!("CBOR integer {} out of range", n))?;
+                JsonValue::Number(Num::Int(n))
+            }
+            (NEGATIVE, _) => {
+                let n = self.argument(info)?;
+                let n =
+                    i64::try_from(n).map_err(|_| anyhow!("CBOR integer -1-{} out of range", n))?;
+                JsonValue::Number(Num::Int(-1 - n))
+            }
+            (TEXT, _) => JsonValue::String(self.text(info)?),
+            (ARRAY, _) => {
+                let len = self.len(info)?;
+                let values = (0..len)
+                    .map(|_| self.value(depth + 1))
+                    .collect::<Result<_>>()?;
+                JsonValue::Array(values)
+            }
+            (MAP, _) => {
+                let len = self.len(info)?;
+                let mut map = HashMap::new();
+                for _ in 0..len {
+                    let key_start = self.pos;
+                    let key = self.take_array::<1>()?[0];
+                    if key >> 5 != TEXT {
+                        bail!("CBOR map key at offset {} is not a text string", key_start);
+                    }
+                    let key = self.text(key & 0x1f)?;
+                    map.insert(key.into(), self.value(depth + 1)?);
+                }
+                JsonValue::Object(map)
+            }
+            (SIMPLE, FALSE) => JsonValue::Bool(false),
+            (SIMPLE, TRUE) => JsonValue::Bool(true),
+            (SIMPLE, NULL) => JsonValue::Null,
+            (SIMPLE, FLOAT32) => {
+                let n = f32::from_be_bytes(self.take_array()?);
+                JsonValue::Number(Num::Float(n as f64))
+            }
+            (SIMPLE, FLOAT64) => {
+                JsonValue::Number(Num::Float(f64::from_be_bytes(self.take_array()?)))
+            }
+            _ => bail!(
+                "unsupported CBOR item 0x{:02x} at offset {}",
+                initial,
+                start
+            ),
+        };
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::parse_json;
+
+    #[test]
+    fn test_cbor_roundtrip() -> Result<()> {
+        let value = parse_json(
+            r#"{
+              "name": "John Doe",
+              "age": 30,
+              "debt": -9223372036854775808,
+              "is_student": false,
+              "marks": [90.0, -80.5, 85.1],
+              "address": {"city": "New York", "zip": null},
+              "note": "Ünïcödé and a string longer than twenty-three bytes"
+            }"#,
+        )?;
+        let cbor = value.to_cbor();
+        assert_eq!(JsonValue::from_cbor(&cbor)?, value);
+        Ok(())
+    }
+
+    #[test]
+    fn test_cbor_known_encodings() -> Result<()> {
+        // examples from RFC 8949 appendix A
+        assert_eq!(parse_json("100")?.to_cbor(), [0x18, 0x64]);
+        assert_eq!(parse_json("-1000")?.to_cbor(), [0x39, 0x03, 0xe7]);
+        assert_eq!(parse_json(r#""a""#)?.to_cbor(), [0x61, 0x61]);
+        assert_eq!(parse_json("[1, 2]")?.to_cbor(), [0x82, 0x01, 0x02]);
+        assert_eq!(
+            parse_json(r#"{"a": 1}"#)?.to_cbor(),
+            [0xa1, 0x61, 0x61, 0x01]
+        );
+        assert_eq!(
+            JsonValue::from_cbor(&[0xfa, 0x47, 0xc3, 0x50, 0x00])?,
+            JsonValue::Number(Num::Float(100000.0))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_cbor_rejects_malformed_input() {
+        // truncated array, trailing byte, byte string, oversized length
+        assert!(JsonValue::from_cbor(&[0x82, 0x01]).is_err());
+        assert!(JsonValue::from_cbor(&[0x01, 0x01]).is_err());
+        assert!(JsonValue::from_cbor(&[0x41, 0x00]).is_err());
+        assert!(
+            JsonValue::from_cbor(&[0x9b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]).is_err()
+        );
+        assert!(JsonValue::from_cbor(&[0x81; 1000]).is_err());
+    }
+}
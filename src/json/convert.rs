@@ -0,0 +1,104 @@
+use super::{JsonKind, JsonValue};
+use anyhow::{anyhow, Result};
+
+/// Types that can be extracted from a [`JsonValue`] without going through
+/// serde.
+pub trait FromJson: Sized {
+    fn from_json(value: &JsonValue) -> Result<Self>;
+}
+
+fn expected(what: &str, value: &JsonValue) -> anyhow::Error {
+    anyhow!("expected {}, found {:?}", what, value.kind())
+}
+
+impl FromJson for JsonValue {
+    fn from_json(value: &JsonValue) -> Result<Self> {
+        Ok(value.clone())
+    }
+}
+
+impl FromJson for bool {
+    fn from_json(value: &JsonValue) -> Result<Self> {
+        value.as_bool().ok_or_else(|| expected("a boolean", value))
+    }
+}
+
+impl FromJson for i64 {
+    fn from_json(value: &JsonValue) -> Result<Self> {
+        value.as_i64().ok_or_else(|| expected("an integer", value))
+    }
+}
+
+/// Integers are accepted too, like [`JsonValue::as_f64`].
+impl FromJson for f64 {
+    fn from_json(value: &JsonValue) -> Result<Self> {
+        value.as_f64().ok_or_else(|| expected("a number", value))
+    }
+}
+
+impl FromJson for String {
+    fn from_json(value: &JsonValue) -> Result<Self> {
+        value
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| expected("a string", value))
+    }
+}
+
+/// `null` is `None`, anything else must convert to `T`.
+impl<T: FromJson> FromJson for Option<T> {
+    fn from_json(value: &JsonValue) -> Result<Self> {
+        match value.kind() {
+            JsonKind::Null => Ok(None),
+            _ => T::from_json(value).map(Some),
+        }
+    }
+}
+
+impl<T: FromJson> FromJson for Vec<T> {
+    fn from_json(value: &JsonValue) -> Result<Self> {
+        value.as_vec_of()
+    }
+}
+
+impl JsonValue {
+    /// Converts an array into a `Vec<T>`, failing on any other value or on
+    /// the first element that is not a `T`.
+    pub fn as_vec_of<T: FromJson>(&self) -> Result<Vec<T>> {
+        let values = self.as_array().ok_or_else(|| expected("an array", self))?;
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, v)| T::from_json(v).map_err(|e| anyhow!("element {}: {}", i, e)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::parse_json;
+
+    #[test]
+    fn test_as_vec_of() -> Result<()> {
+        let value = parse_json(r#"{"marks": [90.0, -80.0, 85.1], "mixed": [1, "two", 3]}"#)?;
+        let marks: Vec<f64> = value.get("marks").unwrap().as_vec_of()?;
+        assert_eq!(marks, [90.0, -80.0, 85.1]);
+
+        let mixed = value.get("mixed").unwrap();
+        let err = mixed.as_vec_of::<i64>().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "element 1: expected an integer, found String"
+        );
+        assert!(value.as_vec_of::<f64>().is_err());
+
+        let nested = parse_json("[[1, 2], [], [null, 3]]")?;
+        let nested: Vec<Vec<Option<i64>>> = nested.as_vec_of()?;
+        assert_eq!(
+            nested,
+            [vec![Some(1), Some(2)], vec![], vec![None, Some(3)]]
+        );
+        Ok(())
+    }
+}
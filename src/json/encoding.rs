@@ -0,0 +1,98 @@
+use anyhow::{anyhow, bail, Result};
+use std::borrow::Cow;
+
+// the longer UTF-32 marks first, as UTF-32LE starts with the UTF-16LE one
+const BOMS: [(&[u8], Encoding); 5] = [
+    (&[0xFF, 0xFE, 0x00, 0x00], Encoding::Utf32),
+    (&[0x00, 0x00, 0xFE, 0xFF], Encoding::Utf32),
+    (&[0xEF, 0xBB, 0xBF], Encoding::Utf8),
+    (&[0xFF, 0xFE], Encoding::Utf16Le),
+    (&[0xFE, 0xFF], Encoding::Utf16Be),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Encoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Utf32,
+}
+
+// the document as UTF-8 without its byte order mark; input without one is
+// taken to be UTF-8 already and is not copied
+pub(super) fn to_utf8(input: &[u8]) -> Result<Cow<'_, [u8]>> {
+    let Some((bom, encoding)) = BOMS.iter().find(|(bom, _)| input.starts_with(bom)) else {
+        return Ok(Cow::Borrowed(input));
+    };
+    let body = &input[bom.len()..];
+    match encoding {
+        Encoding::Utf8 => Ok(Cow::Borrowed(body)),
+        Encoding::Utf16Le => utf16_to_utf8(body, u16::from_le_bytes),
+        Encoding::Utf16Be => utf16_to_utf8(body, u16::from_be_bytes),
+        Encoding::Utf32 => bail!("Failed to parse JSON: UTF-32 input is not supported"),
+    }
+}
+
+#[cfg(feature = "utf16")]
+fn utf16_to_utf8(body: &[u8], unit: fn([u8; 2]) -> u16) -> Result<Cow<'_, [u8]>> {
+    let pairs = body.chunks_exact(2);
+    if !pairs.remainder().is_empty() {
+        bail!("Failed to parse JSON: UTF-16 input has an odd number of bytes");
+    }
+    let units = pairs.map(|pair| unit([pair[0], pair[1]]));
+    let text = char::decode_utf16(units)
+        .collect::<Result<String, _>>()
+        .map_err(|e| anyhow!("Failed to parse JSON: invalid UTF-16: {}", e))?;
+    Ok(Cow::Owned(text.into_bytes()))
+}
+
+#[cfg(not(feature = "utf16"))]
+fn utf16_to_utf8(_: &[u8], _: fn([u8; 2]) -> u16) -> Result<Cow<'_, [u8]>> {
+    Err(anyhow!(
+        "Failed to parse JSON: UTF-16 input needs the `utf16` feature"
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::{parse_json, parse_json_bytes};
+    use anyhow::Result;
+
+    fn utf16le(text: &str) -> Vec<u8> {
+        let mut bytes = vec![0xFF, 0xFE];
+        bytes.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+        bytes
+    }
+
+    #[test]
+    #[cfg(feature = "utf16")]
+    fn test_parse_json_bytes_utf16() -> Result<()> {
+        let text = r#"{"name": "Żółw 🐢", "tags": ["a", "b"]}"#;
+        assert_eq!(parse_json_bytes(&utf16le(text))?, parse_json(text)?);
+
+        let mut be = vec![0xFE, 0xFF];
+        be.extend(text.encode_utf16().flat_map(u16::to_be_bytes));
+        assert_eq!(parse_json_bytes(&be)?, parse_json(text)?);
+
+        let mut odd = utf16le(text);
+        odd.pop();
+        assert!(parse_json_bytes(&odd).is_err());
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(not(feature = "utf16"))]
+    fn test_parse_json_bytes_utf16_needs_feature() {
+        let err = parse_json_bytes(&utf16le("[1]")).unwrap_err();
+        assert!(err.to_string().contains("`utf16` feature"), "{}", err);
+    }
+
+    #[test]
+    fn test_parse_json_bytes_boms() -> Result<()> {
+        assert_eq!(parse_json_bytes(b"\xEF\xBB\xBF[1]")?, parse_json("[1]")?);
+        let utf32 = b"\xFF\xFE\x00\x00[\x00\x00\x00]\x00\x00\x00";
+        let err = parse_json_bytes(utf32).unwrap_err();
+        assert!(err.to_string().contains("UTF-32"), "{}", err);
+        Ok(())
+    }
+}
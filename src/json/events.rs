@@ -0,0 +1,186 @@
+use super::{JsonValueRef, Token, Tokenizer};
+use anyhow::{anyhow, Result};
+use std::{borrow::Cow, ops::Range};
+
+/// A step through the structure of a JSON document.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event<'a> {
+    StartObject,
+    EndObject,
+    StartArray,
+    EndArray,
+    /// The key of the object member whose value comes next.
+    Key(Cow<'a, str>),
+    /// A null, boolean, number or string value.
+    Scalar(JsonValueRef<'a>),
+}
+
+/// Walks a JSON document as a sequence of [`Event`]s, each with the byte span
+/// of the token it stems from.
+///
+/// Unlike the [`Tokenizer`] it checks that the tokens form a valid document:
+/// it yields an error for a misplaced token or a premature end of input and
+/// stops after it.
+#[derive(Debug)]
+pub struct EventIterator<'a> {
+    tokens: Tokenizer<'a>,
+    stack: Vec<Frame>,
+    root_done: bool,
+    failed: bool,
+}
+
+// what the innermost open array or object expects next
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Frame {
+    ObjectKeyOrEnd,
+    ObjectKey,
+    ObjectColon,
+    ObjectValue,
+    ObjectCommaOrEnd,
+    ArrayValueOrEnd,
+    ArrayValue,
+    ArrayCommaOrEnd,
+}
+
+impl<'a> EventIterator<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Self {
+            tokens: Tokenizer::new(input),
+            stack: Vec::new(),
+            root_done: false,
+            failed: false,
+        }
+    }
+
+    fn next_event(&mut self) -> Result<Option<(Event<'a>, Range<usize>)>> {
+        loop {
+            let Some(token) = self.tokens.next() else {
+                if self.root_done {
+                    return Ok(None);
+                }
+                return Err(anyhow!("unexpected end of input"));
+            };
+            let (token, span) = token?;
+            let unexpected = || anyhow!("unexpected {:?} at byte {}", token, span.start);
+
+            let event = match (self.stack.last().copied(), token.clone()) {
+                (None, _) if self.root_done => return Err(unexpected()),
+                (Some(Frame::ObjectKeyOrEnd | Frame::ObjectKey), Token::String(key)) => {
+                    self.set_top(Frame::ObjectColon);
+                    Event::Key(key)
+                }
+                (Some(Frame::ObjectColon), Token::Colon) => {
+                    self.set_top(Frame::ObjectValue);
+                    continue;
+                }
+                (Some(Frame::ObjectCommaOrEnd), Token::Comma) => {
+                    self.set_top(Frame::ObjectKey);
+                    continue;
+                }
+                (Some(Frame::ArrayCommaOrEnd), Token::Comma) => {
+                    self.set_top(Frame::ArrayValue);
+                    continue;
+                }
+                (Some(Frame::ObjectKeyOrEnd | Frame::ObjectCommaOrEnd), Token::EndObject) => {
+                    self.stack.pop();
+                    self.value_done();
+                    Event::EndObject
+                }
+                (Some(Frame::ArrayValueOrEnd | Frame::ArrayCommaOrEnd), Token::EndArray) => {
+                    self.stack.pop();
+                    self.value_done();
+                    Event::EndArray
+                }
+                (
+                    None | Some(Frame::ObjectValue | Frame::ArrayValueOrEnd | Frame::ArrayValue),
+                    token,
+                ) => self.start_value(token).ok_or_else(unexpected)?,
+                _ => return Err(unexpected()),
+            };
+            return Ok(Some((event, span)));
+        }
+    }
+
+    fn start_value(&mut self, token: Token<'a>) -> Option<Event<'a>> {
+        let scalar = match token {
+            Token::BeginObject => {
+                self.stack.push(Frame::ObjectKeyOrEnd);
+                return Some(Event::StartObject);
+            }
+            Token::BeginArray => {
+                self.stack.push(Frame::ArrayValueOrEnd);
+                return Some(Event::StartArray);
+            }
+            Token::String(s) => JsonValueRef::String(s),
+            Token::Number(n) => JsonValueRef::Number(n),
+            Token::Bool(b) => JsonValueRef::Bool(b),
+            Token::Null => JsonValueRef::Null,
+            _ => return None,
+        };
+        self.value_done();
+        Some(Event::Scalar(scalar))
+    }
+
+    // a complete value was read: the enclosing array or object moves on
+    fn value_done(&mut self) {
+        match self.stack.last() {
+            Some(Frame::ObjectValue) => self.set_top(Frame::ObjectCommaOrEnd),
+            Some(_) => self.set_top(Frame::ArrayCommaOrEnd),
+            None => self.root_done = true,
+        }
+    }
+
+    fn set_top(&mut self, frame: Frame) {
+        if let Some(top) = self.stack.last_mut() {
+            *top = frame;
+        }
+    }
+}
+
+impl<'a> Iterator for EventIterator<'a> {
+    type Item = Result<(Event<'a>, Range<usize>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let ret = self.next_event();
+        if ret.is_err() {
+            self.failed = true;
+        }
+        ret.transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::Num;
+
+    #[test]
+    fn test_events_with_spans() -> Result<()> {
+        let events = EventIterator::new(r#"{"a":[1, 2]}"#).collect::<Result<Vec<_>>>()?;
+        assert_eq!(
+            events,
+            vec![
+                (Event::StartObject, 0..1),
+                (Event::Key("a".into()), 1..4),
+                (Event::StartArray, 5..6),
+                (Event::Scalar(JsonValueRef::Number(Num::Int(1))), 6..7),
+                (Event::Scalar(JsonValueRef::Number(Num::Int(2))), 9..10),
+                (Event::EndArray, 10..11),
+                (Event::EndObject, 11..12),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_events_reject_invalid_structure() {
+        for input in [r#"{"a" 1}"#, "[1 2]", "[1,]", "{1: 2}", "[1]]", "[", ""] {
+            let events: Vec<_> = EventIterator::new(input).collect();
+            assert!(events.last().unwrap().is_err(), "{}", input);
+            assert_eq!(events.iter().filter(|e| e.is_err()).count(), 1);
+        }
+    }
+}
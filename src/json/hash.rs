@@ -0,0 +1,107 @@
+use super::{JsonValue, Num};
+
+// FNV-1a: tiny, and unlike `DefaultHasher` its output is fixed across Rust
+// releases and platforms
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+struct Fnv(u64);
+
+impl Fnv {
+    fn write(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 ^= u64::from(*b);
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    fn write_len(&mut self, len: usize) {
+        self.write(&(len as u64).to_le_bytes());
+    }
+
+    fn write_str(&mut self, s: &str) {
+        // the length prefix keeps `["ab","c"]` apart from `["a","bc"]`
+        self.write_len(s.len());
+        self.write(s.as_bytes());
+    }
+}
+
+impl JsonValue {
+    /// Returns a hash of the value's content that ignores the order of object
+    /// members, so documents that compare equal hash equally. The result is
+    /// stable across runs and platforms and can be stored, e.g. as a cache key.
+    ///
+    /// A `NaN` float is not equal to itself, so no such promise holds for
+    /// documents containing one.
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = Fnv(FNV_OFFSET);
+        self.hash_into(&mut hasher);
+        hasher.0
+    }
+
+    fn hash_into(&self, hasher: &mut Fnv) {
+        match self {
+            JsonValue::Null => hasher.write(&[0]),
+            JsonValue::Bool(b) => hasher.write(&[1, u8::from(*b)]),
+            JsonValue::Number(Num::Int(n)) => {
+                hasher.write(&[2]);
+                hasher.write(&n.to_le_bytes());
+            }
+            JsonValue::Number(Num::Float(n)) => {
+                hasher.write(&[3]);
+                // `-0.0 == 0.0`, but their bits differ
+                let n = if *n == 0.0 { 0.0 } else { *n };
+                hasher.write(&n.to_bits().to_le_bytes());
+            }
+            JsonValue::String(s) => {
+                hasher.write(&[4]);
+                hasher.write_str(s);
+            }
+            JsonValue::Array(values) => {
+                hasher.write(&[5]);
+                hasher.write_len(values.len());
+                for v in values {
+                    v.hash_into(hasher);
+                }
+            }
+            JsonValue::Object(map) => {
+                hasher.write(&[6]);
+                hasher.write_len(map.len());
+                let mut members: Vec<_> = map.iter().collect();
+                members.sort_unstable_by_key(|(k, _)| *k);
+                for (k, v) in members {
+                    hasher.write_str(k);
+                    v.hash_into(hasher);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::parse_json;
+    use anyhow::Result;
+
+    #[test]
+    fn test_content_hash_ignores_key_order() -> Result<()> {
+        let a = parse_json(r#"{"a": 1, "b": {"x": [true, null], "y": "s"}}"#)?;
+        let b = parse_json(r#"{"b": {"y": "s", "x": [true, null]}, "a": 1}"#)?;
+        assert_eq!(a.content_hash(), b.content_hash());
+
+        let c = parse_json(r#"{"a": 1, "b": {"x": [null, true], "y": "s"}}"#)?;
+        let d = parse_json(r#"{"a": 1.0, "b": {"x": [true, null], "y": "s"}}"#)?;
+        assert_ne!(a.content_hash(), c.content_hash());
+        assert_ne!(a.content_hash(), d.content_hash());
+        Ok(())
+    }
+
+    #[test]
+    fn test_content_hash_of_negative_zero() -> Result<()> {
+        let positive = parse_json("[0.0]")?;
+        let negative = parse_json("[-0.0]")?;
+        assert_eq!(positive, negative);
+        assert_eq!(positive.content_hash(), negative.content_hash());
+        Ok(())
+    }
+}
@@ -0,0 +1,59 @@
+use super::{parse_json, JsonValue};
+use anyhow::{anyhow, Result};
+use std::io::BufRead;
+
+/// Iterates over the documents of newline-delimited JSON (JSONL/NDJSON), one
+/// per non-blank line. Every line must hold exactly one complete document.
+#[derive(Debug)]
+pub struct JsonLines<R> {
+    reader: R,
+    buf: String,
+    line: usize,
+}
+
+impl<R: BufRead> JsonLines<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buf: String::new(),
+            line: 0,
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for JsonLines<R> {
+    type Item = Result<JsonValue>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.buf.clear();
+            match self.reader.read_line(&mut self.buf) {
+                Ok(0) => return None,
+                Ok(_) => self.line += 1,
+                Err(e) => return Some(Err(e.into())),
+            }
+            // the line ending is insignificant whitespace to `parse_json`
+            if !self.buf.trim().is_empty() {
+                let line = self.line;
+                return Some(parse_json(&self.buf).map_err(|e| anyhow!("line {}: {}", line, e)));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_json_lines_skips_blank_lines() {
+        let input = "{\"id\": 1}\n\n[1, 2] 3\r\n\"done\"\r\n";
+        let docs: Vec<_> = JsonLines::new(Cursor::new(input)).collect();
+        assert_eq!(docs.len(), 3);
+        assert_eq!(docs[0].as_ref().unwrap().path().key("id").as_i64(), Some(1));
+        let err = docs[1].as_ref().unwrap_err().to_string();
+        assert!(err.starts_with("line 3: "), "{}", err);
+        assert_eq!(docs[2].as_ref().unwrap().as_str(), Some("done"));
+    }
+}
@@ -0,0 +1,129 @@
+use super::JsonValue;
+use anyhow::{bail, Result};
+use std::{fmt, str::FromStr};
+
+/// A JSON Pointer (RFC 6901) such as `/address/city` or `/marks/0`, parsed
+/// once so that it can be looked up in many values with
+/// [`JsonValue::get_path`].
+///
+/// `~1` and `~0` stand for a `/` and a `~` within a key. A segment made of
+/// digits also selects the element of an array at that index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonPath {
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Segment {
+    key: String,
+    // the array index the segment stands for, if it is one
+    index: Option<usize>,
+}
+
+impl JsonPath {
+    pub fn parse(pointer: &str) -> Result<Self> {
+        if pointer.is_empty() {
+            return Ok(Self { segments: vec![] });
+        }
+        let Some(pointer) = pointer.strip_prefix('/') else {
+            bail!("JSON pointer `{}` must start with `/`", pointer);
+        };
+        let segments = pointer
+            .split('/')
+            .map(|segment| {
+                if segment.replace("~0", "").replace("~1", "").contains('~') {
+                    bail!("invalid escape in JSON pointer segment `{}`", segment);
+                }
+                let key = segment.replace("~1", "/").replace("~0", "~");
+                // indices have no leading zeros
+                let digits = key.bytes().all(|b| b.is_ascii_digit());
+                let index = (key == "0" || digits && !key.starts_with('0'))
+                    .then(|| key.parse().ok())
+                    .flatten();
+                Ok(Segment { key, index })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { segments })
+    }
+}
+
+impl FromStr for JsonPath {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for JsonPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for segment in &self.segments {
+            write!(f, "/{}", segment.key.replace('~', "~0").replace('/', "~1"))?;
+        }
+        Ok(())
+    }
+}
+
+impl JsonValue {
+    /// Looks up the value `path` points to, `None` if a key or index along
+    /// the way is missing. The empty path points to the value itself.
+    pub fn get_path(&self, path: &JsonPath) -> Option<&JsonValue> {
+        path.segments
+            .iter()
+            .try_fold(self, |value, segment| match value {
+                JsonValue::Object(map) => map.get(segment.key.as_str()),
+                JsonValue::Array(values) => values.get(segment.index?),
+                _ => None,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::parse_json;
+
+    #[test]
+    fn test_get_path_reuses_compiled_path() -> Result<()> {
+        let city: JsonPath = "/address/city".parse()?;
+        let first_mark = JsonPath::parse("/marks/0")?;
+        let docs = [
+            r#"{"address": {"city": "New York"}, "marks": [90, 80]}"#,
+            r#"{"address": {"city": "Paris"}, "marks": []}"#,
+        ];
+        let docs = docs
+            .map(parse_json)
+            .into_iter()
+            .collect::<Result<Vec<_>>>()?;
+
+        let cities: Vec<_> = docs
+            .iter()
+            .map(|doc| doc.get_path(&city).and_then(JsonValue::as_str))
+            .collect();
+        assert_eq!(cities, [Some("New York"), Some("Paris")]);
+        assert_eq!(
+            docs[0].get_path(&first_mark).and_then(JsonValue::as_i64),
+            Some(90)
+        );
+        assert_eq!(docs[1].get_path(&first_mark), None);
+        assert_eq!(docs[0].get_path(&JsonPath::parse("")?), Some(&docs[0]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_path_escapes_and_errors() -> Result<()> {
+        let doc = parse_json(r#"{"a/b": {"~c": [1, 2]}, "01": 3}"#)?;
+        let path = JsonPath::parse("/a~1b/~0c/1")?;
+        assert_eq!(path.to_string(), "/a~1b/~0c/1");
+        assert_eq!(doc.get_path(&path).and_then(JsonValue::as_i64), Some(2));
+        assert_eq!(
+            doc.get_path(&"/01".parse()?).and_then(JsonValue::as_i64),
+            Some(3)
+        );
+        assert_eq!(doc.get_path(&"/a~1b/~0c/01".parse()?), None);
+
+        assert!(JsonPath::parse("a/b").is_err());
+        assert!(JsonPath::parse("/a~2").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,86 @@
+use super::{
+    array_of, count_node, object_of, parse_bool, parse_document, parse_key, parse_null, parse_num,
+    parse_string, Input, JsonStream, JsonValue, Num, ParseState, EXPECTED_VALUE,
+};
+use anyhow::{anyhow, Result};
+use std::{borrow::Cow, collections::BTreeMap};
+use winnow::{combinator::alt, PResult, Parser};
+
+/// A JSON value whose objects keep their members sorted by key, so that
+/// iterating them is lexicographically ordered.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SortedJsonValue {
+    Null,
+    Bool(bool),
+    Number(Num),
+    String(String),
+    Array(Vec<SortedJsonValue>),
+    Object(BTreeMap<String, SortedJsonValue>),
+}
+
+/// Parses a complete JSON document like [`parse_json`](super::parse_json)
+/// into objects sorted by key. A repeated key keeps its last value.
+pub fn parse_json_sorted(input: &str) -> Result<SortedJsonValue> {
+    let (value, _) = parse_document(input, ParseState::default(), parse_value_sorted)
+        .map_err(|e| anyhow!(e.message))?;
+    Ok(value)
+}
+
+fn parse_value_sorted<'i, I: JsonStream<'i>>(input: &mut Input<I>) -> PResult<SortedJsonValue> {
+    let value = alt((
+        parse_null.value(SortedJsonValue::Null),
+        parse_bool.map(SortedJsonValue::Bool),
+        parse_num.map(SortedJsonValue::Number),
+        parse_string.map(|s| SortedJsonValue::String(s.into_owned())),
+        array_of(parse_value_sorted).map(SortedJsonValue::Array),
+        object_of(parse_key.map(Cow::into_owned), parse_value_sorted).map(SortedJsonValue::Object),
+    ))
+    .context(EXPECTED_VALUE)
+    .parse_next(input)?;
+    count_node(input)?;
+    Ok(value)
+}
+
+/// Sorts the members of every object in the tree.
+impl From<JsonValue> for SortedJsonValue {
+    fn from(value: JsonValue) -> Self {
+        match value {
+            JsonValue::Null => SortedJsonValue::Null,
+            JsonValue::Bool(b) => SortedJsonValue::Bool(b),
+            JsonValue::Number(n) => SortedJsonValue::Number(n),
+            JsonValue::String(s) => SortedJsonValue::String(s),
+            JsonValue::Array(values) => {
+                SortedJsonValue::Array(values.into_iter().map(Self::from).collect())
+            }
+            JsonValue::Object(map) => SortedJsonValue::Object(
+                map.into_iter()
+                    .map(|(k, v)| (k.to_string(), Self::from(v)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::parse_json;
+
+    #[test]
+    fn test_parse_json_sorted_iterates_keys_in_order() -> Result<()> {
+        let value = parse_json_sorted(r#"{"b": 1, "a": 2}"#)?;
+        let SortedJsonValue::Object(map) = &value else {
+            panic!("expected an object, got {:?}", value);
+        };
+        let keys: Vec<_> = map.keys().map(String::as_str).collect();
+        assert_eq!(keys, ["a", "b"]);
+        assert_eq!(map["a"], SortedJsonValue::Number(Num::Int(2)));
+
+        let input = r#"[{"z": {"y": null, "x": [true]}, "é": "", "A": 0}]"#;
+        assert_eq!(parse_json_sorted(input)?, parse_json(input)?.into());
+
+        let input = "\u{feff}{\"a\": []}";
+        assert_eq!(parse_json_sorted(input)?, parse_json(input)?.into());
+        Ok(())
+    }
+}
@@ -0,0 +1,100 @@
+use super::{parse_document, parse_value, JsonValue, ParseOptions, ParseState};
+use anyhow::{anyhow, Result};
+
+/// Figures about a parsed document, see [`parse_json_with_stats`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ParseStats {
+    /// Length of the document, surrounding whitespace included.
+    pub bytes: usize,
+    /// Number of JSON tokens in the input: scalars, keys and the punctuation
+    /// `[]{},:`. Members dropped for a repeated key still count, a trailing
+    /// comma that lenient mode skipped does not.
+    pub tokens: usize,
+    /// Deepest nesting of arrays and objects, 0 for a scalar root.
+    pub max_depth: usize,
+}
+
+/// Parses a complete JSON document like
+/// [`parse_json_with`](super::parse_json_with), also returning
+/// [`ParseStats`] about it.
+///
+/// The statistics are gathered from the input while parsing it, so they
+/// describe the text whatever [`KeyPolicy`](super::KeyPolicy) made of it; the
+/// other parsers don't gather them and pay nothing for them.
+pub fn parse_json_with_stats(
+    input: &str,
+    options: ParseOptions,
+) -> Result<(JsonValue, ParseStats)> {
+    let state = ParseState {
+        stats: Some(ParseStats {
+            bytes: input.len(),
+            ..Default::default()
+        }),
+        ..options.into()
+    };
+    let (value, state) =
+        parse_document(input, state, parse_value).map_err(|e| anyhow!(e.message))?;
+    Ok((value, state.stats.unwrap_or_default()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::KeyPolicy;
+
+    #[test]
+    fn test_parse_stats_of_sample() -> Result<()> {
+        let input = r#"{
+          "name": "John Doe",
+          "age": 30,
+          "is_student": false,
+          "marks": [90.0, -80.0, 85.1],
+          "address": {
+            "city": "New York",
+            "zip": 10001
+          }
+        }"#;
+        let (_, stats) = parse_json_with_stats(input, ParseOptions::default())?;
+        assert_eq!(
+            stats,
+            ParseStats {
+                bytes: input.len(),
+                tokens: 35,
+                max_depth: 2,
+            }
+        );
+
+        let (_, stats) = parse_json_with_stats(" 42 ", ParseOptions::default())?;
+        assert_eq!((stats.tokens, stats.max_depth), (1, 0));
+        let (_, stats) = parse_json_with_stats("[[]]", ParseOptions::default())?;
+        assert_eq!((stats.tokens, stats.max_depth), (4, 2));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_stats_count_input_tokens() -> Result<()> {
+        let input = r#"{"a": 1, "a": [2, {"b": 3}], "c": 4}"#;
+        // braces, 3 keys, 3 colons, 2 commas, 1, 4, and `[2, {"b": 3}]`
+        let expected = ParseStats {
+            bytes: input.len(),
+            tokens: 2 + 3 + 3 + 2 + 2 + 9,
+            max_depth: 3,
+        };
+        for key_policy in [
+            KeyPolicy::KeepFirst,
+            KeyPolicy::KeepLast,
+            KeyPolicy::MergeToArray,
+        ] {
+            let options = ParseOptions {
+                key_policy,
+                ..Default::default()
+            };
+            let (_, stats) = parse_json_with_stats(input, options)?;
+            assert_eq!(stats, expected, "{:?}", key_policy);
+        }
+
+        let (_, stats) = parse_json_with_stats("[1, [2,], 3,]", ParseOptions::lenient())?;
+        assert_eq!((stats.tokens, stats.max_depth), (9, 2));
+        Ok(())
+    }
+}
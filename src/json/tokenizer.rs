@@ -0,0 +1,122 @@
+use super::{
+    new_input, parse_bool, parse_null, parse_num, parse_string, position, whitespace, Input,
+    JsonStream, Num, ParseState,
+};
+use anyhow::{anyhow, Result};
+use std::{borrow::Cow, ops::Range};
+use winnow::{combinator::alt, error::ContextError, PResult, Parser};
+
+/// A lexical JSON token.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token<'a> {
+    BeginObject,
+    EndObject,
+    BeginArray,
+    EndArray,
+    Colon,
+    Comma,
+    String(Cow<'a, str>),
+    Number(Num),
+    Bool(bool),
+    Null,
+}
+
+/// Splits a JSON text into tokens, each with its byte span in the input.
+///
+/// The tokenizer only checks that every token is well formed, not that they
+/// form a valid document. It stops after the first invalid token.
+#[derive(Debug)]
+pub struct Tokenizer<'a> {
+    input: Input<&'a str>,
+    failed: bool,
+}
+
+impl<'a> Tokenizer<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Self {
+            input: new_input(input, ParseState::default()),
+            failed: false,
+        }
+    }
+}
+
+impl<'a> Iterator for Tokenizer<'a> {
+    type Item = Result<(Token<'a>, Range<usize>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        whitespace::<_, ContextError>(&mut self.input).ok()?;
+        if self.input.input.is_empty() {
+            return None;
+        }
+
+        let start = position(&self.input);
+        let found = self.input.input.chars().next();
+        match parse_token(&mut self.input) {
+            Ok(token) => Some(Ok((token, start..position(&self.input)))),
+            Err(_) => {
+                self.failed = true;
+                Some(Err(anyhow!(
+                    "Invalid token at byte {}: unexpected `{}`",
+                    start,
+                    found?
+                )))
+            }
+        }
+    }
+}
+
+fn parse_token<'i, I: JsonStream<'i>>(input: &mut Input<I>) -> PResult<Token<'i>> {
+    alt((
+        '{'.value(Token::BeginObject),
+        '}'.value(Token::EndObject),
+        '['.value(Token::BeginArray),
+        ']'.value(Token::EndArray),
+        ':'.value(Token::Colon),
+        ','.value(Token::Comma),
+        parse_null.value(Token::Null),
+        parse_bool.map(Token::Bool),
+        parse_num.map(Token::Number),
+        parse_string.map(Token::String),
+    ))
+    .parse_next(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize() -> Result<()> {
+        let tokens = Tokenizer::new(r#"{"a":[1]}"#).collect::<Result<Vec<_>>>()?;
+        assert_eq!(
+            tokens,
+            vec![
+                (Token::BeginObject, 0..1),
+                (Token::String("a".into()), 1..4),
+                (Token::Colon, 4..5),
+                (Token::BeginArray, 5..6),
+                (Token::Number(Num::Int(1)), 6..7),
+                (Token::EndArray, 7..8),
+                (Token::EndObject, 8..9),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_tokenize_skips_whitespace_and_stops_on_error() {
+        let mut tokens = Tokenizer::new(" true ,\n @ null");
+        assert_eq!(tokens.next().unwrap().unwrap(), (Token::Bool(true), 1..5));
+        assert_eq!(tokens.next().unwrap().unwrap(), (Token::Comma, 6..7));
+        let err = tokens.next().unwrap().unwrap_err();
+        assert_eq!(err.to_string(), "Invalid token at byte 9: unexpected `@`");
+        assert!(tokens.next().is_none());
+
+        let err = Tokenizer::new(r#"["ab"#).nth(1).unwrap().unwrap_err();
+        assert_eq!(err.to_string(), "Invalid token at byte 1: unexpected `\"`");
+    }
+}
@@ -0,0 +1,651 @@
+use super::{JsonKind, JsonValue, Num};
+use std::{
+    collections::{hash_map, BTreeSet, HashMap},
+    iter,
+    sync::Arc,
+    vec,
+};
+
+impl JsonValue {
+    /// Returns the member `key` of an object, or `None` for any other value.
+    pub fn get(&self, key: &str) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Object(map) => map.get(key),
+            _ => None,
+        }
+    }
+
+    /// Returns the element at `index` of an array, or `None` for any other value.
+    pub fn get_index(&self, index: usize) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Array(values) => values.get(index),
+            _ => None,
+        }
+    }
+
+    /// A copy of the member `key` of an object if it has the same
+    /// [`kind`](Self::kind) as `default`, otherwise `default`.
+    pub fn get_or(&self, key: &str, default: JsonValue) -> JsonValue {
+        match self.get(key) {
+            Some(value) if value.kind() == default.kind() => value.clone(),
+            _ => default,
+        }
+    }
+
+    /// The member `key` of an object if it is a string, otherwise `default`.
+    pub fn get_str_or<'a>(&'a self, key: &str, default: &'a str) -> &'a str {
+        self.get(key).and_then(JsonValue::as_str).unwrap_or(default)
+    }
+
+    /// The member `key` of an object if it is an integer, otherwise `default`.
+    pub fn get_i64_or(&self, key: &str, default: i64) -> i64 {
+        self.get(key).and_then(JsonValue::as_i64).unwrap_or(default)
+    }
+
+    /// The member `key` of an object if it is a number, otherwise `default`.
+    pub fn get_f64_or(&self, key: &str, default: f64) -> f64 {
+        self.get(key).and_then(JsonValue::as_f64).unwrap_or(default)
+    }
+
+    /// The member `key` of an object if it is a boolean, otherwise `default`.
+    pub fn get_bool_or(&self, key: &str, default: bool) -> bool {
+        self.get(key)
+            .and_then(JsonValue::as_bool)
+            .unwrap_or(default)
+    }
+
+    pub fn kind(&self) -> JsonKind {
+        match self {
+            JsonValue::Null => JsonKind::Null,
+            JsonValue::Bool(_) => JsonKind::Bool,
+            JsonValue::Number(_) => JsonKind::Number,
+            JsonValue::String(_) => JsonKind::String,
+            JsonValue::Array(_) => JsonKind::Array,
+            JsonValue::Object(_) => JsonKind::Object,
+        }
+    }
+
+    /// Whether the value is an object with a member `key`.
+    pub fn has_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Whether the value is an array with an element equal to `value`.
+    pub fn contains(&self, value: &JsonValue) -> bool {
+        self.as_array().is_some_and(|values| values.contains(value))
+    }
+
+    /// The number of elements of an array, members of an object or
+    /// characters of a string. `null` has a length of 0, booleans and
+    /// numbers one of 1.
+    pub fn len(&self) -> usize {
+        match self {
+            JsonValue::Null => 0,
+            JsonValue::Bool(_) | JsonValue::Number(_) => 1,
+            JsonValue::String(s) => s.chars().count(),
+            JsonValue::Array(values) => values.len(),
+            JsonValue::Object(map) => map.len(),
+        }
+    }
+
+    /// Whether [`len`](Self::len) is 0: `null` or an empty array, object or
+    /// string.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Like `==`, but numbers, integers and floats alike, are equal when
+    /// they differ by at most `epsilon`. Arrays and objects are compared
+    /// element by element and member by member.
+    pub fn approx_eq(&self, other: &JsonValue, epsilon: f64) -> bool {
+        match (self, other) {
+            // integers above 2^53 would lose digits as floats
+            (JsonValue::Number(Num::Int(a)), JsonValue::Number(Num::Int(b))) => {
+                a.abs_diff(*b) as f64 <= epsilon
+            }
+            (JsonValue::Number(_), JsonValue::Number(_)) => self
+                .as_f64()
+                .zip(other.as_f64())
+                .is_some_and(|(a, b)| (a - b).abs() <= epsilon),
+            (JsonValue::Array(a), JsonValue::Array(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.approx_eq(b, epsilon))
+            }
+            (JsonValue::Object(a), JsonValue::Object(b)) => {
+                a.len() == b.len()
+                    && a.iter()
+                        .all(|(k, a)| b.get(k).is_some_and(|b| a.approx_eq(b, epsilon)))
+            }
+            (a, b) => a == b,
+        }
+    }
+
+    /// Like `==`, but arrays at any depth are compared as multisets: `[1, 2]`
+    /// equals `[2, 1]`, yet `[1, 1, 2]` does not equal `[1, 2, 2]`. Takes
+    /// quadratic time in the length of the arrays.
+    pub fn set_eq(&self, other: &JsonValue) -> bool {
+        match (self, other) {
+            (JsonValue::Array(a), JsonValue::Array(b)) => {
+                if a.len() != b.len() {
+                    return false;
+                }
+                // pair every element of `a` with a distinct one of `b`
+                let mut matched = vec![false; b.len()];
+                a.iter().all(|a| {
+                    let found = (0..b.len()).find(|&i| !matched[i] && a.set_eq(&b[i]));
+                    found.map(|i| matched[i] = true).is_some()
+                })
+            }
+            (JsonValue::Object(a), JsonValue::Object(b)) => {
+                a.len() == b.len() && a.iter().all(|(k, a)| b.get(k).is_some_and(|b| a.set_eq(b)))
+            }
+            (a, b) => a == b,
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, JsonValue::Null)
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            JsonValue::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            JsonValue::Number(Num::Int(n)) => Some(*n),
+            _ => None,
+        }
+    }
+
+    /// Returns any number as `f64`, integers included.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            JsonValue::Number(Num::Int(n)) => Some(*n as f64),
+            JsonValue::Number(Num::Float(n)) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            JsonValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&Vec<JsonValue>> {
+        match self {
+            JsonValue::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&HashMap<Arc<str>, JsonValue>> {
+        match self {
+            JsonValue::Object(map) => Some(map),
+            _ => None,
+        }
+    }
+
+    /// Calls `f` on every value of the tree depth-first, each value before its
+    /// children.
+    pub fn walk(&self, mut f: impl FnMut(&JsonValue)) {
+        self.walk_with(&mut f);
+    }
+
+    fn walk_with(&self, f: &mut impl FnMut(&JsonValue)) {
+        f(self);
+        match self {
+            JsonValue::Array(values) => values.iter().for_each(|v| v.walk_with(f)),
+            JsonValue::Object(map) => map.values().for_each(|v| v.walk_with(f)),
+            _ => {}
+        }
+    }
+
+    /// Like [`walk`](Self::walk), but lets `f` change the values in place. The
+    /// children visited are those of the value as `f` left it.
+    pub fn walk_mut(&mut self, mut f: impl FnMut(&mut JsonValue)) {
+        self.walk_mut_with(&mut f);
+    }
+
+    fn walk_mut_with(&mut self, f: &mut impl FnMut(&mut JsonValue)) {
+        f(self);
+        match self {
+            JsonValue::Array(values) => values.iter_mut().for_each(|v| v.walk_mut_with(f)),
+            JsonValue::Object(map) => map.values_mut().for_each(|v| v.walk_mut_with(f)),
+            _ => {}
+        }
+    }
+
+    /// Returns every string in the tree that satisfies `pred`, in document
+    /// order for arrays and in unspecified order for object members.
+    pub fn find_strings(&self, pred: impl Fn(&str) -> bool) -> Vec<&str> {
+        let mut found = Vec::new();
+        self.collect_strings(&pred, false, &mut found);
+        found
+    }
+
+    /// Like [`find_strings`](Self::find_strings), but also tests object keys.
+    pub fn find_strings_and_keys(&self, pred: impl Fn(&str) -> bool) -> Vec<&str> {
+        let mut found = Vec::new();
+        self.collect_strings(&pred, true, &mut found);
+        found
+    }
+
+    /// Every object key anywhere in the tree, each once, in sorted order.
+    pub fn all_keys(&self) -> BTreeSet<String> {
+        let mut keys = BTreeSet::new();
+        self.walk(|v| {
+            if let JsonValue::Object(map) = v {
+                keys.extend(map.keys().map(|k| k.to_string()));
+            }
+        });
+        keys
+    }
+
+    fn collect_strings<'a>(
+        &'a self,
+        pred: &impl Fn(&str) -> bool,
+        keys: bool,
+        found: &mut Vec<&'a str>,
+    ) {
+        match self {
+            JsonValue::String(s) if pred(s) => found.push(s),
+            JsonValue::Array(values) => {
+                for v in values {
+                    v.collect_strings(pred, keys, found);
+                }
+            }
+            JsonValue::Object(map) => {
+                for (k, v) in map {
+                    if keys && pred(k) {
+                        found.push(k);
+                    }
+                    v.collect_strings(pred, keys, found);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// The [`Entry`](hash_map::Entry) of member `key` of an object, to
+    /// insert or update it in place, or `None` for any other value.
+    pub fn entry(&mut self, key: &str) -> Option<hash_map::Entry<'_, Arc<str>, JsonValue>> {
+        match self {
+            JsonValue::Object(map) => Some(map.entry(Arc::from(key))),
+            _ => None,
+        }
+    }
+
+    /// Consumes an object into its members, in unspecified order. Any other
+    /// value yields nothing.
+    pub fn into_entries(self) -> hash_map::IntoIter<Arc<str>, JsonValue> {
+        match self {
+            JsonValue::Object(map) => map.into_iter(),
+            _ => HashMap::new().into_iter(),
+        }
+    }
+
+    /// Starts a chain of lookups into nested values, e.g.
+    /// `value.path().key("address").key("city").as_str()`.
+    pub fn path(&self) -> Lookup<'_> {
+        Lookup(Some(self))
+    }
+}
+
+/// Iterates over an array's elements; any other value, objects and `null`
+/// included, is yielded once as a single element. Use
+/// [`into_entries`](JsonValue::into_entries) for the members of an object.
+impl IntoIterator for JsonValue {
+    type Item = JsonValue;
+    type IntoIter = IntoIter;
+
+    fn into_iter(self) -> IntoIter {
+        match self {
+            JsonValue::Array(values) => IntoIter(Items::Array(values.into_iter())),
+            value => IntoIter(Items::Single(iter::once(value))),
+        }
+    }
+}
+
+/// The iterator returned by [`JsonValue::into_iter`].
+#[derive(Debug)]
+pub struct IntoIter(Items);
+
+#[derive(Debug)]
+enum Items {
+    Array(vec::IntoIter<JsonValue>),
+    Single(iter::Once<JsonValue>),
+}
+
+impl Iterator for IntoIter {
+    type Item = JsonValue;
+
+    fn next(&mut self) -> Option<JsonValue> {
+        match &mut self.0 {
+            Items::Array(values) => values.next(),
+            Items::Single(value) => value.next(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.0 {
+            Items::Array(values) => values.size_hint(),
+            Items::Single(value) => value.size_hint(),
+        }
+    }
+}
+
+/// A chain of lookups started by [`JsonValue::path`]. Every step
+/// short-circuits to `None` once a key or index is missing or a value has the
+/// wrong type.
+#[derive(Debug, Clone, Copy)]
+pub struct Lookup<'a>(Option<&'a JsonValue>);
+
+impl<'a> Lookup<'a> {
+    pub fn key(self, key: &str) -> Self {
+        Lookup(self.0.and_then(|v| v.get(key)))
+    }
+
+    pub fn index(self, index: usize) -> Self {
+        Lookup(self.0.and_then(|v| v.get_index(index)))
+    }
+
+    pub fn get(self) -> Option<&'a JsonValue> {
+        self.0
+    }
+
+    pub fn as_bool(self) -> Option<bool> {
+        self.0?.as_bool()
+    }
+
+    pub fn as_i64(self) -> Option<i64> {
+        self.0?.as_i64()
+    }
+
+    pub fn as_f64(self) -> Option<f64> {
+        self.0?.as_f64()
+    }
+
+    pub fn as_str(self) -> Option<&'a str> {
+        self.0?.as_str()
+    }
+
+    pub fn as_array(self) -> Option<&'a Vec<JsonValue>> {
+        self.0?.as_array()
+    }
+
+    pub fn as_object(self) -> Option<&'a HashMap<Arc<str>, JsonValue>> {
+        self.0?.as_object()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::{parse_json, JsonValue, Num};
+    use anyhow::Result;
+
+    const SAMPLE: &str = r#"{
+      "name": "John Doe",
+      "age": 30,
+      "is_student": false,
+      "marks": [90.0, -80.0, 85.1],
+      "address": {
+        "city": "New York",
+        "zip": 10001
+      }
+    }"#;
+
+    #[test]
+    fn test_path_reaches_nested_value() -> Result<()> {
+        let value = parse_json(SAMPLE)?;
+        assert_eq!(
+            value.path().key("address").key("city").as_str(),
+            Some("New York")
+        );
+        assert_eq!(value.path().key("address").key("zip").as_i64(), Some(10001));
+        assert_eq!(value.path().key("marks").index(2).as_f64(), Some(85.1));
+        Ok(())
+    }
+
+    #[test]
+    fn test_path_short_circuits_on_missing_key() -> Result<()> {
+        let value = parse_json(SAMPLE)?;
+        assert_eq!(value.path().key("location").key("city").as_str(), None);
+        // type mismatch: `name` is not an object
+        assert_eq!(value.path().key("name").key("city").as_str(), None);
+        // the value exists but is not a string
+        assert_eq!(value.path().key("address").key("zip").as_str(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_len_and_is_empty() -> Result<()> {
+        let value = parse_json(SAMPLE)?;
+        assert_eq!(value.len(), 5);
+        assert_eq!(value.get("marks").unwrap().len(), 3);
+        // characters, not bytes
+        assert_eq!(parse_json(r#""héllo""#)?.len(), 5);
+        assert_eq!(value.get("age").unwrap().len(), 1);
+        assert!(!value.is_empty());
+        assert!(parse_json("[]")?.is_empty());
+        assert!(parse_json("\"\"")?.is_empty());
+        assert!(JsonValue::Null.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_entry_inserts_and_updates() -> Result<()> {
+        let mut value = parse_json(SAMPLE)?;
+        value
+            .entry("nickname")
+            .unwrap()
+            .or_insert(JsonValue::String("JD".to_string()));
+        value
+            .entry("age")
+            .unwrap()
+            .and_modify(|age| *age = JsonValue::Number(Num::Int(31)));
+        assert_eq!(value.get("nickname").unwrap().as_str(), Some("JD"));
+        assert_eq!(value.get("age").unwrap().as_i64(), Some(31));
+
+        let mut marks = value.get("marks").unwrap().clone();
+        assert!(marks.entry("first").is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_all_keys_spans_every_level() -> Result<()> {
+        let value = parse_json(SAMPLE)?;
+        assert_eq!(
+            value.all_keys().into_iter().collect::<Vec<_>>(),
+            [
+                "address",
+                "age",
+                "city",
+                "is_student",
+                "marks",
+                "name",
+                "zip"
+            ]
+        );
+
+        let value = parse_json(r#"[{"id": 1, "tags": [{"id": 2}]}, {"id": 3}]"#)?;
+        assert_eq!(
+            value.all_keys().into_iter().collect::<Vec<_>>(),
+            ["id", "tags"]
+        );
+        assert!(parse_json("[1, 2]")?.all_keys().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_approx_eq_tolerates_rounding() -> Result<()> {
+        let sum = JsonValue::Number(Num::Float(0.1 + 0.2));
+        let expected = JsonValue::Number(Num::Float(0.3));
+        assert_ne!(sum, expected);
+        assert!(sum.approx_eq(&expected, 1e-9));
+        assert!(!sum.approx_eq(&JsonValue::Number(Num::Float(0.31)), 1e-9));
+        assert!(JsonValue::Number(Num::Int(3)).approx_eq(&JsonValue::Number(Num::Float(3.0)), 0.0));
+
+        let a = parse_json(r#"{"x": [0.30000000000000004, "a"], "y": null}"#)?;
+        let b = parse_json(r#"{"x": [0.3, "a"], "y": null}"#)?;
+        assert!(a.approx_eq(&b, 1e-9));
+        let c = parse_json(r#"{"x": [0.3, "b"], "y": null}"#)?;
+        assert!(!a.approx_eq(&c, 1e-9));
+        let d = parse_json(r#"{"x": [0.3, "a"], "z": null}"#)?;
+        assert!(!a.approx_eq(&d, 1e-9));
+
+        let big = parse_json("9007199254740993")?;
+        let next = parse_json("9007199254740992")?;
+        assert!(!big.approx_eq(&next, 0.0));
+        assert!(big.approx_eq(&next, 1.0));
+        let ends = parse_json("[-9223372036854775808, 9223372036854775807]")?;
+        assert!(ends.approx_eq(&ends, 0.0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_eq_ignores_array_order() -> Result<()> {
+        let a = parse_json(r#"{"tags": ["x", "y", "x"], "nested": [[1, 2], {"k": [3, 4]}]}"#)?;
+        let b = parse_json(r#"{"nested": [{"k": [4, 3]}, [2, 1]], "tags": ["x", "x", "y"]}"#)?;
+        assert_ne!(a, b);
+        assert!(a.set_eq(&b));
+        assert!(b.set_eq(&a));
+
+        let c = parse_json(r#"{"tags": ["x", "y", "y"], "nested": [[1, 2], {"k": [3, 4]}]}"#)?;
+        assert!(!a.set_eq(&c));
+        let d = parse_json(r#"{"tags": ["x", "y"], "nested": [[1, 2], {"k": [3, 4]}]}"#)?;
+        assert!(!a.set_eq(&d));
+        assert!(!parse_json("[1, 2]")?.set_eq(&parse_json("[1.0, 2]")?));
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_or_falls_back_on_missing_or_mistyped() -> Result<()> {
+        let value = parse_json(SAMPLE)?;
+        // present with the right type
+        assert_eq!(value.get_str_or("name", "anonymous"), "John Doe");
+        assert_eq!(value.get_i64_or("age", 0), 30);
+        assert!(!value.get_bool_or("is_student", true));
+        assert_eq!(
+            value.get_or("age", JsonValue::Number(Num::Int(0))),
+            JsonValue::Number(Num::Int(30))
+        );
+        // present with the wrong type
+        assert_eq!(value.get_str_or("age", "unknown"), "unknown");
+        assert_eq!(value.get_i64_or("name", -1), -1);
+        assert_eq!(value.get_or("marks", JsonValue::Null), JsonValue::Null);
+        // missing
+        assert_eq!(value.get_str_or("email", "none"), "none");
+        assert_eq!(value.get_f64_or("height", 1.8), 1.8);
+        assert_eq!(
+            value.get_or("tags", JsonValue::Array(vec![])),
+            JsonValue::Array(vec![])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_has_key_and_contains() -> Result<()> {
+        let value = parse_json(SAMPLE)?;
+        assert!(value.has_key("address"));
+        assert!(!value.has_key("location"));
+        assert!(!value.path().key("name").get().unwrap().has_key("name"));
+
+        let marks = value.get("marks").unwrap();
+        assert!(marks.contains(&JsonValue::Number(Num::Float(85.1))));
+        assert!(!marks.contains(&JsonValue::Number(Num::Float(85.0))));
+        // an object does not contain its members' values
+        assert!(!value.contains(&JsonValue::Number(Num::Int(30))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_walk_mut_rewrites_every_string() -> Result<()> {
+        let mut value = parse_json(SAMPLE)?;
+        value.walk_mut(|v| {
+            if let JsonValue::String(s) = v {
+                *s = s.to_uppercase();
+            }
+        });
+        assert_eq!(value.path().key("name").as_str(), Some("JOHN DOE"));
+        assert_eq!(
+            value.path().key("address").key("city").as_str(),
+            Some("NEW YORK")
+        );
+
+        let mut count = 0;
+        value.walk(|_| count += 1);
+        // the root, five members, three marks and two address members
+        assert_eq!(count, 11);
+        Ok(())
+    }
+
+    #[test]
+    fn test_into_iter_yields_array_elements() -> Result<()> {
+        let value = parse_json("[1, \"two\", null]")?;
+        let items: Vec<_> = value.into_iter().collect();
+        assert_eq!(
+            items,
+            [
+                JsonValue::Number(Num::Int(1)),
+                JsonValue::String("two".to_string()),
+                JsonValue::Null
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_into_iter_yields_scalar_once() -> Result<()> {
+        let mut count = 0;
+        for value in parse_json("42")? {
+            assert_eq!(value, JsonValue::Number(Num::Int(42)));
+            count += 1;
+        }
+        assert_eq!(count, 1);
+
+        let object = parse_json(r#"{"a": 1}"#)?;
+        assert_eq!(object.clone().into_iter().next(), Some(object.clone()));
+        let entries: Vec<_> = object.into_entries().collect();
+        assert_eq!(entries, [("a".into(), JsonValue::Number(Num::Int(1)))]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_strings_walks_whole_tree() -> Result<()> {
+        let value = parse_json(
+            r#"{
+              "owner": "ann@example.com",
+              "team": [
+                {"name": "Bob", "email": "bob@example.com"},
+                {"name": "Eve", "aliases": ["eve@example.org", "eve"]}
+              ],
+              "me@host": 1
+            }"#,
+        )?;
+
+        let mut found = value.find_strings(|s| s.contains('@'));
+        found.sort_unstable();
+        assert_eq!(
+            found,
+            ["ann@example.com", "bob@example.com", "eve@example.org"]
+        );
+
+        let mut found = value.find_strings_and_keys(|s| s.contains('@'));
+        found.sort_unstable();
+        assert_eq!(
+            found,
+            [
+                "ann@example.com",
+                "bob@example.com",
+                "eve@example.org",
+                "me@host"
+            ]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,265 @@
+use super::{JsonValue, Num};
+use std::{
+    fmt,
+    io::{self, Write},
+};
+
+/// Serializes the value as compact JSON straight into `w`, the same text as
+/// its [`Display`](std::fmt::Display) output.
+///
+/// Strings are written in runs between the characters that need escaping
+/// rather than copied into an escaped buffer first, so large strings cost no
+/// extra memory.
+///
+/// A `NaN` or infinite float fails with [`io::ErrorKind::InvalidData`], as
+/// strict JSON can't represent it; what was written up to it is left in `w`.
+pub fn write_json(w: &mut impl Write, value: &JsonValue) -> io::Result<()> {
+    write_value(w, value, false)
+}
+
+/// Like [`write_json`], but writes non-finite floats as `NaN`, `Infinity`
+/// and `-Infinity` instead of failing, for consumers that accept them; this
+/// crate's parser does in [`lenient`](super::ParseOptions::lenient) mode.
+pub fn write_json_lenient(w: &mut impl Write, value: &JsonValue) -> io::Result<()> {
+    write_value(w, value, true)
+}
+
+fn write_value(w: &mut impl Write, value: &JsonValue, lenient: bool) -> io::Result<()> {
+    match value {
+        JsonValue::Null => w.write_all(b"null"),
+        JsonValue::Bool(b) => write!(w, "{}", b),
+        JsonValue::Number(Num::Float(n)) if !lenient && !n.is_finite() => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} can't be represented in JSON", n),
+        )),
+        JsonValue::Number(n) => write!(w, "{}", n),
+        JsonValue::String(s) => write_escaped(w, s),
+        JsonValue::Array(values) => {
+            w.write_all(b"[")?;
+            for (i, v) in values.iter().enumerate() {
+                if i > 0 {
+                    w.write_all(b",")?;
+                }
+                write_value(w, v, lenient)?;
+            }
+            w.write_all(b"]")
+        }
+        JsonValue::Object(map) => {
+            w.write_all(b"{")?;
+            for (i, (k, v)) in map.iter().enumerate() {
+                if i > 0 {
+                    w.write_all(b",")?;
+                }
+                write_escaped(w, k)?;
+                w.write_all(b":")?;
+                write_value(w, v, lenient)?;
+            }
+            w.write_all(b"}")
+        }
+    }
+}
+
+impl JsonValue {
+    /// Serializes the value as JSON with one element or member per line,
+    /// indented by `indent` spaces per level.
+    pub fn to_pretty_string(&self, indent: usize) -> String {
+        self.to_pretty_string_limited(indent, usize::MAX)
+    }
+
+    /// Like [`to_pretty_string`](Self::to_pretty_string), but non-empty arrays
+    /// and objects nested `max_depth` levels or deeper are elided as `[…]`
+    /// and `{…}`, the root being at depth 0. The result is meant for reading,
+    /// it is not valid JSON once something was elided.
+    pub fn to_pretty_string_limited(&self, indent: usize, max_depth: usize) -> String {
+        let mut out = String::new();
+        // writing to a `String` can't fail
+        let _ = write_pretty(&mut out, self, indent, max_depth, 0);
+        out
+    }
+}
+
+fn write_pretty(
+    out: &mut String,
+    value: &JsonValue,
+    indent: usize,
+    max_depth: usize,
+    depth: usize,
+) -> fmt::Result {
+    use fmt::Write;
+
+    let pad = |out: &mut String, depth: usize| write!(out, "\n{:1$}", "", indent * depth);
+    match value {
+        JsonValue::Array(values) if values.is_empty() => out.push_str("[]"),
+        JsonValue::Object(map) if map.is_empty() => out.push_str("{}"),
+        JsonValue::Array(_) if depth >= max_depth => out.push_str("[…]"),
+        JsonValue::Object(_) if depth >= max_depth => out.push_str("{…}"),
+        JsonValue::Array(values) => {
+            out.push('[');
+            for (i, v) in values.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                pad(out, depth + 1)?;
+                write_pretty(out, v, indent, max_depth, depth + 1)?;
+            }
+            pad(out, depth)?;
+            out.push(']');
+        }
+        JsonValue::Object(map) => {
+            out.push('{');
+            for (i, (k, v)) in map.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                pad(out, depth + 1)?;
+                super::write_string(out, k)?;
+                out.push_str(": ");
+                write_pretty(out, v, indent, max_depth, depth + 1)?;
+            }
+            pad(out, depth)?;
+            out.push('}');
+        }
+        scalar => write!(out, "{}", scalar)?,
+    }
+    Ok(())
+}
+
+fn write_escaped(w: &mut impl Write, s: &str) -> io::Result<()> {
+    w.write_all(b"\"")?;
+    escape_str(s, |part| w.write_all(part.as_bytes()))?;
+    w.write_all(b"\"")
+}
+
+// hands `emit` the runs of `s` that need no escaping and the escape sequences
+// in between; all escaped characters are ASCII, so scanning bytes never
+// splits a character
+pub(super) fn escape_str<E>(s: &str, mut emit: impl FnMut(&str) -> Result<(), E>) -> Result<(), E> {
+    const HEX: &[u8; 16] = b"0123456789abcdef";
+    let mut start = 0;
+    for (i, b) in s.bytes().enumerate() {
+        let escape = match b {
+            b'"' => "\\\"",
+            b'\\' => "\\\\",
+            b'\n' => "\\n",
+            b'\r' => "\\r",
+            b'\t' => "\\t",
+            0x08 => "\\b",
+            0x0c => "\\f",
+            0x00..=0x1f => "",
+            _ => continue,
+        };
+        if start < i {
+            emit(&s[start..i])?;
+        }
+        if escape.is_empty() {
+            let code = [
+                b'\\',
+                b'u',
+                b'0',
+                b'0',
+                HEX[usize::from(b >> 4)],
+                HEX[usize::from(b & 0xf)],
+            ];
+            emit(std::str::from_utf8(&code).unwrap_or_default())?;
+        } else {
+            emit(escape)?;
+        }
+        start = i + 1;
+    }
+    if start < s.len() {
+        emit(&s[start..])?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::{parse_json, parse_json_with_warnings, ParseOptions, WarningKind};
+    use anyhow::Result;
+
+    #[test]
+    fn test_write_json_escapes_strings_in_runs() -> Result<()> {
+        let s = "plain \"quoted\"\ttab\u{1}end é";
+        let mut parts = Vec::new();
+        escape_str(s, |part| {
+            parts.push(part.to_string());
+            Ok::<_, ()>(())
+        })
+        .unwrap();
+        assert_eq!(
+            parts,
+            ["plain ", "\\\"", "quoted", "\\\"", "\\t", "tab", "\\u0001", "end é"]
+        );
+
+        let mut buf = Vec::new();
+        write_json(&mut buf, &JsonValue::String(s.to_string()))?;
+        assert_eq!(
+            String::from_utf8(buf)?,
+            r#""plain \"quoted\"\ttab\u0001end é""#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_pretty_string() -> Result<()> {
+        let value = parse_json(r#"{"a": [1, {}, []]}"#)?;
+        assert_eq!(
+            value.to_pretty_string(2),
+            "{\n  \"a\": [\n    1,\n    {},\n    []\n  ]\n}"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_to_pretty_string_limited_elides_deep_values() -> Result<()> {
+        let value = parse_json(
+            r#"{"name": "John Doe", "marks": [90.0, 85.1], "address": {"city": "New York"}}"#,
+        )?;
+        let pretty = value.to_pretty_string_limited(2, 1);
+        assert!(pretty.contains("\n  \"name\": \"John Doe\""));
+        assert!(pretty.contains("\n  \"address\": {…}"));
+        assert!(pretty.contains("\n  \"marks\": […]"));
+        assert!(!pretty.contains("New York"));
+
+        assert_eq!(value.to_pretty_string_limited(2, 0), "{…}");
+        assert!(value.to_pretty_string_limited(2, 2).contains("New York"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_json_matches_display() -> Result<()> {
+        let value = parse_json(r#"{"a": [1, 2.5, null, true], "b\n": {"c": "\\"}}"#)?;
+        let mut buf = Vec::new();
+        write_json(&mut buf, &value)?;
+        assert_eq!(String::from_utf8(buf)?, value.to_string());
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_json_non_finite_floats() -> Result<()> {
+        let value = JsonValue::Array(vec![
+            JsonValue::Number(Num::Float(f64::NAN)),
+            JsonValue::Number(Num::Float(f64::INFINITY)),
+            JsonValue::Number(Num::Float(f64::NEG_INFINITY)),
+        ]);
+        let err = write_json(&mut Vec::new(), &value).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(err.to_string(), "NaN can't be represented in JSON");
+
+        let mut buf = Vec::new();
+        write_json_lenient(&mut buf, &value)?;
+        let written = String::from_utf8(buf)?;
+        assert_eq!(written, "[NaN,Infinity,-Infinity]");
+        assert_eq!(value.to_string(), written);
+
+        let (parsed, warnings) = parse_json_with_warnings(&written, ParseOptions::lenient())?;
+        let floats: Vec<f64> = parsed.as_vec_of()?;
+        assert!(floats[0].is_nan());
+        assert_eq!(floats[1..], [f64::INFINITY, f64::NEG_INFINITY]);
+        assert_eq!(warnings.len(), 3);
+        assert!(warnings.iter().all(|w| w.kind == WarningKind::NonFinite));
+        assert!(parse_json("[NaN]").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,1341 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    str::FromStr,
+};
+use winnow::{
+    ascii::{digit1, escaped_transform, multispace0, multispace1, space0, space1},
+    combinator::{
+        alt, delimited, empty, eof, not, opt, peek, preceded, rest, separated, terminated,
+    },
+    error::{ContextError, StrContext, StrContextValue},
+    stream::AsChar,
+    token::{one_of, take, take_till, take_until, take_while},
+    PResult, Parser,
+};
+
+mod builder;
+mod columns;
+mod csv;
+mod diff;
+mod fieldmap;
+mod lines;
+mod stats;
+
+pub use builder::NginxLogBuilder;
+pub use columns::LogColumns;
+pub use csv::logs_to_csv;
+pub use diff::FieldDiff;
+pub use fieldmap::{parse_with_fieldmap, FieldMap};
+#[cfg(feature = "flate2")]
+pub use lines::parse_nginx_log_gz;
+pub use lines::{parse_nginx_logs, parse_nginx_logs_delimited, LogLines};
+pub use stats::{bytes_by_url, status_histogram, top_urls};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum HttpMethod {
+    Get,
+    Post,
+    Put,
+    Delete,
+    Head,
+    Connect,
+    Options,
+    Trace,
+    Patch,
+    /// A method outside the list above, spelled as in the log.
+    Other(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum HttpProto {
+    /// Requests without a protocol part, as sent by HTTP/0.9 clients.
+    HTTP0_9,
+    HTTP1_0,
+    HTTP1_1,
+    HTTP2_0,
+    HTTP3_0,
+}
+
+impl HttpProto {
+    /// The major and minor version, e.g. `(1, 1)` for HTTP/1.1.
+    pub fn version(&self) -> (u8, u8) {
+        match self {
+            HttpProto::HTTP0_9 => (0, 9),
+            HttpProto::HTTP1_0 => (1, 0),
+            HttpProto::HTTP1_1 => (1, 1),
+            HttpProto::HTTP2_0 => (2, 0),
+            HttpProto::HTTP3_0 => (3, 0),
+        }
+    }
+}
+
+/// The size of the response body as logged. A negative or otherwise
+/// malformed size fails the parse instead of being wrapped or mapped to
+/// [`BodyBytes::Unknown`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum BodyBytes {
+    Bytes(u64),
+    /// `chunked`, written for streamed responses of unknown length.
+    Chunked,
+    /// `-`
+    Unknown,
+}
+
+impl BodyBytes {
+    /// The byte count, if one was logged.
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            BodyBytes::Bytes(n) => Some(*n),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NginxLog {
+    pub addr: IpAddr,
+    /// The `ident` field, `None` when logged as `-`.
+    pub ident: Option<String>,
+    /// `$remote_user` of an authenticated request, `None` when logged as `-`.
+    pub remote_user: Option<String>,
+    pub datetime: DateTime<Utc>,
+    pub method: HttpMethod,
+    pub url: String,
+    pub protocol: HttpProto,
+    pub status: u16,
+    pub body_bytes: BodyBytes,
+    /// `$http_referer`, `None` when the line ends before it as in minimal
+    /// formats. A logged `-` is kept as is.
+    pub referer: Option<String>,
+    /// `$http_user_agent`, `None` when the line ends before it.
+    pub user_agent: Option<String>,
+    /// `$request_length` when the format logs it after the user agent.
+    pub request_length: Option<u64>,
+    /// `$request_time` in seconds, when the format logs it in brackets at the
+    /// end of the line, e.g. `[0.123]`.
+    pub request_time: Option<f64>,
+    /// Whatever follows the known fields, e.g. custom variables appended to
+    /// the combined format.
+    pub extra: Option<String>,
+}
+
+impl NginxLog {
+    /// The time of the request as Unix seconds.
+    pub fn epoch(&self) -> i64 {
+        self.datetime.timestamp()
+    }
+
+    /// The major and minor HTTP version of the request, which compares in
+    /// version order, e.g. `log.http_version() >= (2, 0)`.
+    pub fn http_version(&self) -> (u8, u8) {
+        self.protocol.version()
+    }
+
+    /// Tells how [`url`](Self::url) is to be read, as a path, a full URL, a
+    /// `CONNECT` authority or the `OPTIONS *` asterisk.
+    pub fn target_form(&self) -> TargetForm {
+        match self.url.as_str() {
+            "*" => TargetForm::Asterisk,
+            url if url.starts_with('/') => TargetForm::Origin,
+            _ if self.method == HttpMethod::Connect => TargetForm::Authority,
+            _ => TargetForm::Absolute,
+        }
+    }
+
+    /// The host and port a `CONNECT` request asks to tunnel to, e.g.
+    /// `("example.com", 443)` for `CONNECT example.com:443`. Fails for other
+    /// methods and for an authority without a valid port.
+    pub fn connect_target(&self) -> Result<(String, u16)> {
+        if self.method != HttpMethod::Connect {
+            return Err(anyhow::anyhow!("not a CONNECT request: {}", self.method));
+        }
+        let (host, port) = self
+            .url
+            .rsplit_once(':')
+            .filter(|(host, _)| !host.is_empty())
+            .ok_or_else(|| anyhow::anyhow!("expected host:port, found `{}`", self.url))?;
+        let port = port
+            .parse()
+            .map_err(|e| anyhow::anyhow!("invalid port in `{}`: {}", self.url, e))?;
+        Ok((host.to_string(), port))
+    }
+
+    /// The host of the referer URL without port, e.g. `example.com` for
+    /// `https://example.com:8443/page?x=1`. `None` if the referer is missing,
+    /// `-` or not an absolute URL.
+    pub fn referer_host(&self) -> Option<&str> {
+        let (authority, _) = split_url(self.referer.as_deref()?)?;
+        let host = authority
+            .rsplit_once('@')
+            .map_or(authority, |(_, host)| host);
+        let host = match host.strip_prefix('[') {
+            // an IPv6 literal, which has colons of its own
+            Some(v6) => v6.split_once(']')?.0,
+            None => host.split_once(':').map_or(host, |(host, _)| host),
+        };
+        (!host.is_empty()).then_some(host)
+    }
+
+    /// The path of the referer URL without query or fragment, e.g. `/page` for
+    /// `https://example.com/page?x=1`. `None` if the referer is missing, `-`
+    /// or not an absolute URL.
+    pub fn referer_path(&self) -> Option<&str> {
+        let (_, path) = split_url(self.referer.as_deref()?)?;
+        Some(if path.is_empty() { "/" } else { path })
+    }
+
+    /// Whether the referer is a page of the site `host` itself rather than an
+    /// outside one. Hosts are compared without port and case; a referer that
+    /// is missing, `-` or not an absolute URL is not internal.
+    pub fn referer_is_internal(&self, host: &str) -> bool {
+        self.referer_host()
+            .is_some_and(|referer| referer.eq_ignore_ascii_case(host))
+    }
+
+    /// Whether the request path starts with any of `prefixes`, e.g.
+    /// `&["/api/"]`. The query and fragment of the URL are not matched.
+    pub fn is_api_request(&self, prefixes: &[&str]) -> bool {
+        let path = stats::path_of(&self.url);
+        prefixes.iter().any(|prefix| path.starts_with(prefix))
+    }
+
+    /// The reason phrase of the status code, e.g. `Not Found` for 404.
+    pub fn status_text(&self) -> &'static str {
+        match self.status {
+            100 => "Continue",
+            101 => "Switching Protocols",
+            200 => "OK",
+            201 => "Created",
+            202 => "Accepted",
+            204 => "No Content",
+            206 => "Partial Content",
+            301 => "Moved Permanently",
+            302 => "Found",
+            303 => "See Other",
+            304 => "Not Modified",
+            307 => "Temporary Redirect",
+            308 => "Permanent Redirect",
+            400 => "Bad Request",
+            401 => "Unauthorized",
+            403 => "Forbidden",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            408 => "Request Timeout",
+            409 => "Conflict",
+            410 => "Gone",
+            413 => "Payload Too Large",
+            414 => "URI Too Long",
+            429 => "Too Many Requests",
+            444 => "No Response",
+            499 => "Client Closed Request",
+            500 => "Internal Server Error",
+            501 => "Not Implemented",
+            502 => "Bad Gateway",
+            503 => "Service Unavailable",
+            504 => "Gateway Timeout",
+            _ => "Unknown",
+        }
+    }
+
+    /// Renders the main fields as labeled lines for people to read.
+    pub fn summary(&self) -> String {
+        format!(
+            "IP:         {}\n\
+             Request:    {} {} {}\n\
+             Status:     {} {}\n\
+             Bytes:      {}\n\
+             User-Agent: {}",
+            self.addr,
+            self.method,
+            self.url,
+            self.protocol,
+            self.status,
+            self.status_text(),
+            self.body_bytes,
+            self.user_agent.as_deref().unwrap_or("-"),
+        )
+    }
+
+    /// Replaces the values of the named query parameters in
+    /// [`url`](Self::url) with `REDACTED`, e.g. to strip tokens before
+    /// sharing logs. The rest of the URL is kept as is.
+    pub fn redact_query_params(&mut self, keys: &[&str]) {
+        let Some((path, query)) = self.url.split_once('?') else {
+            return;
+        };
+        let (query, fragment) = match query.split_once('#') {
+            Some((query, fragment)) => (query, Some(fragment)),
+            None => (query, None),
+        };
+        let params: Vec<_> = query
+            .split('&')
+            .map(|param| match param.split_once('=') {
+                Some((key, _)) if keys.contains(&key) => format!("{}=REDACTED", key),
+                _ => param.to_string(),
+            })
+            .collect();
+        let mut url = format!("{}?{}", path, params.join("&"));
+        if let Some(fragment) = fragment {
+            url.push('#');
+            url.push_str(fragment);
+        }
+        self.url = url;
+    }
+}
+
+/// The form of a request target, see [`NginxLog::target_form`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetForm {
+    /// A path with an optional query, e.g. `/index.html?q=1`.
+    Origin,
+    /// A full URL as sent to proxies, e.g. `http://example.com/`.
+    Absolute,
+    /// `host:port` of a `CONNECT` request.
+    Authority,
+    /// `*` of a server-wide `OPTIONS` request.
+    Asterisk,
+}
+
+/// Controls how [`parse_nginx_log_with`] treats lines that deviate from the
+/// combined format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogOptions {
+    /// Reject content after the known fields instead of keeping it in
+    /// [`NginxLog::extra`].
+    pub strict: bool,
+    /// How the timestamp field is written.
+    pub time_format: TimeFormat,
+    /// The character around the request, referer and user agent, `"` by
+    /// default. Inside a field it is escaped with a backslash.
+    pub quote: char,
+}
+
+impl Default for LogOptions {
+    fn default() -> Self {
+        Self {
+            strict: false,
+            time_format: TimeFormat::default(),
+            quote: '"',
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TimeFormat {
+    /// `$time_local`, e.g. `[17/May/2015:08:05:32 +0000]`, or
+    /// `$time_iso8601` without brackets, e.g. `2015-05-17T08:05:32+00:00`.
+    #[default]
+    Local,
+    /// `$msec`, Unix seconds with a millisecond fraction, e.g. `1431849932.123`.
+    Msec,
+}
+
+pub fn parse_nginx_log(s: &str) -> PResult<NginxLog> {
+    parse_nginx_log_with(s, LogOptions::default())
+}
+
+pub fn parse_nginx_log_with(s: &str, options: LogOptions) -> PResult<NginxLog> {
+    let input = &mut (&*s);
+    // tolerate lines that were indented when copied or reformatted
+    space0(input)?;
+    let ip = parse_ip(input)?;
+    let ident = parse_user(input)?;
+    let remote_user = parse_user(input)?;
+    let datetime = match options.time_format {
+        TimeFormat::Local => parse_datetime(input)?,
+        TimeFormat::Msec => parse_msec(input)?,
+    };
+    let (method, url, protocol) = parse_http(input, options.quote)?;
+    let status = parse_status(input)?;
+    let body_bytes = parse_body_bytes(input)?;
+    let referer = parse_trailing_quoted_string(input, options.quote)?;
+    let user_agent = parse_trailing_quoted_string(input, options.quote)?;
+    let request_length = parse_request_length(input)?;
+    let request_time = parse_request_time(input)?;
+    let extra = parse_extra(input, options.strict)?;
+    Ok(NginxLog {
+        addr: ip,
+        ident,
+        remote_user,
+        datetime,
+        method,
+        url,
+        protocol,
+        status,
+        body_bytes,
+        referer,
+        user_agent,
+        request_length,
+        request_time,
+        extra,
+    })
+}
+
+// IPv6 addresses are told apart by their colons; some proxies log them in
+// brackets as in URLs, e.g. `[::1]`
+fn parse_ip(s: &mut &str) -> PResult<IpAddr> {
+    let field = peek(take_till(0.., [' ', '\t'])).parse_next(s)?;
+    let ret = if field.starts_with('[') {
+        IpAddr::V6(delimited('[', parse_ipv6, ']').parse_next(s)?)
+    } else if field.contains(':') {
+        IpAddr::V6(parse_ipv6(s)?)
+    } else {
+        IpAddr::V4(parse_ipv4(s)?)
+    };
+    space0(s)?;
+    Ok(ret)
+}
+
+fn parse_ipv4(s: &mut &str) -> PResult<Ipv4Addr> {
+    // exactly four octets: a fifth one would otherwise be left behind as `.5`
+    let ret: Vec<u8> = terminated(separated(4, parse_octet, '.'), not('.'))
+        .context(StrContext::Label("IPv4 address"))
+        .context(StrContext::Expected(StrContextValue::Description(
+            "four dot-separated octets",
+        )))
+        .parse_next(s)?;
+    Ok(Ipv4Addr::new(ret[0], ret[1], ret[2], ret[3]))
+}
+
+fn parse_ipv6(s: &mut &str) -> PResult<Ipv6Addr> {
+    take_while(1.., (AsChar::is_hex_digit, ':', '.'))
+        .parse_to()
+        .context(StrContext::Label("IPv6 address"))
+        .parse_next(s)
+}
+
+fn parse_octet(s: &mut &str) -> PResult<u8> {
+    digit1
+        .try_map(|d: &str| d.parse().map_err(|_| InvalidOctet(d.to_string())))
+        .parse_next(s)
+}
+
+fn parse_user(s: &mut &str) -> PResult<Option<String>> {
+    let ret = take_till(1.., [' ', '\t']).parse_next(s)?;
+    space0(s)?;
+    Ok((ret != "-").then(|| ret.to_string()))
+}
+
+// `$time_local` in brackets or a bare `$time_iso8601`, told apart by the
+// bracket; some builds patch `$time_local` to log fractional seconds, `%.f`
+// keeps them when present
+fn parse_datetime(s: &mut &str) -> PResult<DateTime<Utc>> {
+    let local = delimited('[', take_until(1.., ']'), ']')
+        .try_map(|t| DateTime::parse_from_str(t, "%d/%b/%Y:%H:%M:%S%.f %z"));
+    let iso8601 = take_till(1.., [' ', '\t']).try_map(DateTime::parse_from_rfc3339);
+    let ret = alt((local, iso8601)).parse_next(s)?;
+    space0(s)?;
+    Ok(ret.with_timezone(&Utc))
+}
+
+fn parse_msec(s: &mut &str) -> PResult<DateTime<Utc>> {
+    let ret = (digit1.parse_to(), opt(preceded('.', digit1)))
+        .verify_map(|(secs, frac): (i64, Option<&str>)| {
+            // pad or cut the fraction to nanoseconds without going through f64
+            let nanos = format!("{:0<9.9}", frac.unwrap_or("")).parse().ok()?;
+            DateTime::from_timestamp(secs, nanos)
+        })
+        .parse_next(s)?;
+    space0(s)?;
+    Ok(ret)
+}
+
+fn parse_http(s: &mut &str, quote: char) -> PResult<(HttpMethod, String, HttpProto)> {
+    // unescape the whole request first so an escaped quote can't end it early
+    let request = parse_quoted_string(s, quote)?;
+    // HTTP/0.9 requests stop after the target
+    let protocol = opt(parse_protocol).map(|p| p.unwrap_or(HttpProto::HTTP0_9));
+    let parser = (parse_method.flat_map(parse_target), protocol);
+    let ((method, url), protocol) = terminated(parser, eof).parse_next(&mut request.as_str())?;
+    Ok((method, url, protocol))
+}
+
+// methods are case-sensitive tokens (RFC 9110 section 9.1), but some clients
+// send them in lowercase: known ones are matched regardless of case, unknown
+// ones are kept verbatim. Malformed clients may also leave out the space
+// before the target, as in `GET/path`, which is only split off a known method
+fn parse_method(s: &mut &str) -> PResult<HttpMethod> {
+    let token = take_while(1.., is_token_char).parse_next(s)?;
+    let known = METHODS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(token));
+    let method = match known {
+        Some((_, method)) if s.starts_with('/') => return Ok(method.clone()),
+        Some((_, method)) => method.clone(),
+        None => HttpMethod::Other(token.to_string()),
+    };
+    space1(s)?;
+    Ok(method)
+}
+
+// the methods with a variant of their own
+const METHODS: [(&str, HttpMethod); 9] = [
+    ("GET", HttpMethod::Get),
+    ("POST", HttpMethod::Post),
+    ("PUT", HttpMethod::Put),
+    ("DELETE", HttpMethod::Delete),
+    ("HEAD", HttpMethod::Head),
+    ("CONNECT", HttpMethod::Connect),
+    ("OPTIONS", HttpMethod::Options),
+    ("TRACE", HttpMethod::Trace),
+    ("PATCH", HttpMethod::Patch),
+];
+
+// `tchar` of RFC 9110 section 5.6.2
+fn is_token_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c)
+}
+
+// the request-target forms of RFC 9112 section 3.2: CONNECT takes the
+// authority form `host:port` only, the other forms (including the `*` of
+// OPTIONS) are kept as written
+fn parse_target<'s>(
+    method: HttpMethod,
+) -> impl Parser<&'s str, (HttpMethod, String), ContextError> {
+    move |s: &mut &'s str| {
+        let url = match method {
+            HttpMethod::Connect => parse_authority(s)?,
+            _ => parse_url(s)?,
+        };
+        space0(s)?;
+        Ok((method.clone(), url))
+    }
+}
+
+fn parse_authority(s: &mut &str) -> PResult<String> {
+    let host = take_till(1.., [':', ' ', '/']);
+    let ret = (host, ':', digit1)
+        .take()
+        .context(StrContext::Expected(StrContextValue::Description(
+            "host:port",
+        )))
+        .parse_next(s)?;
+    Ok(ret.to_string())
+}
+
+// malformed clients may send targets with literal spaces, so the target runs
+// up to the last space before a protocol token, or to the end of the request
+// for HTTP/0.9
+fn parse_url(s: &mut &str) -> PResult<String> {
+    let request = s.trim_end_matches(' ');
+    let len = match request.rsplit_once(' ') {
+        Some((url, protocol)) if protocol.starts_with("HTTP/") => url.trim_end_matches(' ').len(),
+        _ => request.len(),
+    };
+    let ret = take(len)
+        .verify(|url: &str| !url.is_empty())
+        .parse_next(s)?;
+    space0(s)?;
+    Ok(ret.to_string())
+}
+
+fn parse_protocol(s: &mut &str) -> PResult<HttpProto> {
+    let ret = alt(("HTTP/0.9", "HTTP/1.0", "HTTP/1.1", "HTTP/2.0", "HTTP/3.0"))
+        .parse_to()
+        .parse_next(s)?;
+    space0(s)?;
+    Ok(ret)
+}
+
+fn parse_status(s: &mut &str) -> PResult<u16> {
+    let ret = digit1.parse_to().parse_next(s)?;
+    space0(s)?;
+    Ok(ret)
+}
+
+// the whole field is checked so that e.g. `-5` is not read as `-` followed by
+// garbage; negative sizes are rejected, never wrapped around
+fn parse_body_bytes(s: &mut &str) -> PResult<BodyBytes> {
+    let ret = take_till(1.., [' ', '\t'])
+        .try_map(|field: &str| match field {
+            "-" => Ok(BodyBytes::Unknown),
+            "chunked" => Ok(BodyBytes::Chunked),
+            _ if field.starts_with('-') => Err(InvalidBodyBytes::Negative(field.to_string())),
+            _ if field.bytes().all(|b| b.is_ascii_digit()) => field
+                .parse()
+                .map(BodyBytes::Bytes)
+                .map_err(|_| InvalidBodyBytes::TooLarge(field.to_string())),
+            _ => Err(InvalidBodyBytes::NotANumber(field.to_string())),
+        })
+        .parse_next(s)?;
+    space0(s)?;
+    Ok(ret)
+}
+
+// optional trailing field; nginx logs `-` when the value is not known. Only
+// a whole field is taken, so that e.g. an unbracketed `0.004` is left to
+// `extra` instead of being read as `0`
+fn parse_request_length(s: &mut &str) -> PResult<Option<u64>> {
+    let field = alt(("-".value(None), digit1.parse_to().map(Some)));
+    let ret = opt(terminated(field, peek(alt((multispace1, eof)))))
+        .parse_next(s)?
+        .flatten();
+    multispace0(s)?;
+    Ok(ret)
+}
+
+// optional trailing field in brackets like `$time_local`; timing fields
+// logged without brackets are left to `extra`
+fn parse_request_time(s: &mut &str) -> PResult<Option<f64>> {
+    let seconds = (digit1, opt(('.', digit1))).take().parse_to();
+    let ret = opt(delimited('[', seconds, ']')).parse_next(s)?;
+    multispace0(s)?;
+    Ok(ret)
+}
+
+fn parse_extra(s: &mut &str, strict: bool) -> PResult<Option<String>> {
+    if strict {
+        eof.context(StrContext::Expected(StrContextValue::Description(
+            "end of line",
+        )))
+        .parse_next(s)?;
+        return Ok(None);
+    }
+    let ret = rest.parse_next(s)?.trim_end();
+    Ok((!ret.is_empty()).then(|| ret.to_string()))
+}
+
+// nginx writes a `"` inside a quoted field as `\"`; other backslashes are kept
+fn parse_quoted_string(s: &mut &str, quote: char) -> PResult<String> {
+    let escape = alt(("\\", quote.take(), empty.value("\\")));
+    let content = escaped_transform(take_till(1.., [quote, '\\']), '\\', escape);
+    let ret = delimited(quote, content, quote).parse_next(s)?;
+    // the user agent may end a line whose newline was not stripped
+    multispace0(s)?;
+    Ok(ret)
+}
+
+// minimal formats end the line after the byte count or the referer
+fn parse_trailing_quoted_string(s: &mut &str, quote: char) -> PResult<Option<String>> {
+    multispace0(s)?;
+    if s.is_empty() {
+        return Ok(None);
+    }
+    parse_quoted_string(s, quote).map(Some)
+}
+
+// `scheme://authority/path?query#fragment` into authority and path
+fn split_url(url: &str) -> Option<(&str, &str)> {
+    let scheme = (
+        one_of(AsChar::is_alpha),
+        take_while(0.., (AsChar::is_alphanum, '+', '-', '.')),
+    );
+    let authority = take_till(0.., ['/', '?', '#']);
+    let path = take_till(0.., ['?', '#']);
+    let ret: PResult<_> = preceded((scheme, "://"), (authority, path)).parse_next(&mut &*url);
+    ret.ok()
+}
+
+#[derive(Debug)]
+struct InvalidOctet(String);
+
+impl fmt::Display for InvalidOctet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid IPv4 octet `{}`: must be 0-255", self.0)
+    }
+}
+
+impl std::error::Error for InvalidOctet {}
+
+#[derive(Debug)]
+enum InvalidBodyBytes {
+    Negative(String),
+    TooLarge(String),
+    NotANumber(String),
+}
+
+impl fmt::Display for InvalidBodyBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvalidBodyBytes::Negative(s) => write!(f, "invalid body size `{}`: negative", s),
+            InvalidBodyBytes::TooLarge(s) => write!(f, "invalid body size `{}`: too large", s),
+            InvalidBodyBytes::NotANumber(s) => write!(
+                f,
+                "invalid body size `{}`: not a number, `-` or `chunked`",
+                s
+            ),
+        }
+    }
+}
+
+impl std::error::Error for InvalidBodyBytes {}
+
+impl FromStr for HttpProto {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "HTTP/0.9" => Ok(HttpProto::HTTP0_9),
+            "HTTP/1.0" => Ok(HttpProto::HTTP1_0),
+            "HTTP/1.1" => Ok(HttpProto::HTTP1_1),
+            "HTTP/2.0" => Ok(HttpProto::HTTP2_0),
+            "HTTP/3.0" => Ok(HttpProto::HTTP3_0),
+            _ => Err(anyhow::anyhow!("Invalid HTTP protocol")),
+        }
+    }
+}
+
+/// Methods are case-sensitive: only the exact names map to the known
+/// variants, any other token becomes [`HttpMethod::Other`].
+impl FromStr for HttpMethod {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match METHODS.iter().find(|(name, _)| *name == s) {
+            Some((_, method)) => Ok(method.clone()),
+            None if !s.is_empty() && s.chars().all(is_token_char) => {
+                Ok(HttpMethod::Other(s.to_string()))
+            }
+            None => Err(anyhow::anyhow!("Invalid HTTP method")),
+        }
+    }
+}
+
+impl TryFrom<&str> for HttpProto {
+    type Error = anyhow::Error;
+
+    fn try_from(s: &str) -> Result<Self> {
+        s.parse()
+    }
+}
+
+impl TryFrom<&str> for HttpMethod {
+    type Error = anyhow::Error;
+
+    fn try_from(s: &str) -> Result<Self> {
+        s.parse()
+    }
+}
+
+impl fmt::Display for BodyBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BodyBytes::Bytes(n) => write!(f, "{}", n),
+            BodyBytes::Chunked => f.write_str("chunked"),
+            BodyBytes::Unknown => f.write_str("-"),
+        }
+    }
+}
+
+impl fmt::Display for HttpProto {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            HttpProto::HTTP0_9 => "HTTP/0.9",
+            HttpProto::HTTP1_0 => "HTTP/1.0",
+            HttpProto::HTTP1_1 => "HTTP/1.1",
+            HttpProto::HTTP2_0 => "HTTP/2.0",
+            HttpProto::HTTP3_0 => "HTTP/3.0",
+        };
+        f.write_str(s)
+    }
+}
+
+impl fmt::Display for HttpMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            HttpMethod::Get => "GET",
+            HttpMethod::Post => "POST",
+            HttpMethod::Put => "PUT",
+            HttpMethod::Delete => "DELETE",
+            HttpMethod::Head => "HEAD",
+            HttpMethod::Connect => "CONNECT",
+            HttpMethod::Options => "OPTIONS",
+            HttpMethod::Trace => "TRACE",
+            HttpMethod::Patch => "PATCH",
+            HttpMethod::Other(s) => s,
+        };
+        f.write_str(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn parse_ip_should_work() -> Result<()> {
+        let mut s = "1.1.1.1";
+        let ip = parse_ip(&mut s).unwrap();
+        assert_eq!(s, "");
+        assert_eq!(ip, IpAddr::V4(Ipv4Addr::new(1, 1, 1, 1)));
+        Ok(())
+    }
+
+    #[test]
+    fn parse_ip_should_accept_ipv6() -> Result<()> {
+        let mut s = "[::1] - -";
+        assert_eq!(parse_ip(&mut s).unwrap(), IpAddr::V6(Ipv6Addr::LOCALHOST));
+        assert_eq!(s, "- -");
+
+        let expected = IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0x8a2e, 0x370));
+        let mut s = "[2001:0db8:0000:0000:0000:0000:8a2e:0370]";
+        assert_eq!(parse_ip(&mut s).unwrap(), expected);
+        let mut s = "2001:db8::8a2e:370";
+        assert_eq!(parse_ip(&mut s).unwrap(), expected);
+
+        let mut s = "[::1";
+        assert!(parse_ip(&mut s).is_err());
+        let mut s = "[1.1.1.1]";
+        assert!(parse_ip(&mut s).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn parse_ip_should_reject_out_of_range_octet() -> Result<()> {
+        let mut s = "256.1.1.1";
+        let err = parse_ip(&mut s).unwrap_err().into_inner().unwrap();
+        assert!(
+            err.to_string().contains("invalid IPv4 octet `256`"),
+            "{}",
+            err
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parse_ip_should_reject_wrong_octet_count() -> Result<()> {
+        let mut s = "1.2.3";
+        assert!(parse_ip(&mut s).is_err());
+
+        let mut s = "1.2.3.4.5";
+        assert!(parse_ip(&mut s).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn parse_datetime_should_work() -> Result<()> {
+        let mut s = "[17/May/2015:08:05:32 +0000]";
+        let dt = parse_datetime(&mut s).unwrap();
+        assert_eq!(s, "");
+        assert_eq!(dt, Utc.with_ymd_and_hms(2015, 5, 17, 8, 5, 32).unwrap());
+        Ok(())
+    }
+
+    #[test]
+    fn parse_datetime_should_accept_iso8601() -> Result<()> {
+        let mut s = "2014-03-07T16:05:49+08:00 \"GET";
+        let dt = parse_datetime(&mut s).unwrap();
+        assert_eq!(s, "\"GET");
+        assert_eq!(dt, Utc.with_ymd_and_hms(2014, 3, 7, 8, 5, 49).unwrap());
+
+        let s =
+            r#"93.184.216.34 - - 2014-03-07T16:05:49+08:00 "GET / HTTP/1.1" 200 2 "-" "curl/8.0""#;
+        let log = parse_nginx_log(s).unwrap();
+        assert_eq!(log.datetime, dt);
+
+        let mut s = "[07/Mar/2014:16:05:49 +0800]";
+        assert_eq!(parse_datetime(&mut s).unwrap(), dt);
+        let mut s = "2014-03-07 ";
+        assert!(parse_datetime(&mut s).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn parse_datetime_should_keep_fractional_seconds() -> Result<()> {
+        let mut s = "[17/May/2015:08:05:32.123 +0000]";
+        let dt = parse_datetime(&mut s).unwrap();
+        assert_eq!(s, "");
+        assert_eq!(dt.timestamp(), 1431849932);
+        assert_eq!(dt.timestamp_subsec_nanos(), 123_000_000);
+        Ok(())
+    }
+
+    #[test]
+    fn redact_query_params_should_work() -> Result<()> {
+        let mut log = NginxLogBuilder::new().url("/cb?token=secret&ok=1").build();
+        log.redact_query_params(&["token", "password"]);
+        assert_eq!(log.url, "/cb?token=REDACTED&ok=1");
+
+        let mut log = NginxLogBuilder::new().url("/cb").build();
+        log.redact_query_params(&["token"]);
+        assert_eq!(log.url, "/cb");
+        Ok(())
+    }
+
+    #[test]
+    fn http_version_should_work() -> Result<()> {
+        let versions = [
+            (HttpProto::HTTP0_9, (0, 9)),
+            (HttpProto::HTTP1_0, (1, 0)),
+            (HttpProto::HTTP1_1, (1, 1)),
+            (HttpProto::HTTP2_0, (2, 0)),
+            (HttpProto::HTTP3_0, (3, 0)),
+        ];
+        for (protocol, version) in versions {
+            let log = NginxLogBuilder::new().protocol(protocol).build();
+            assert_eq!(log.http_version(), version);
+        }
+        assert!(HttpProto::HTTP2_0.version() >= (2, 0));
+        assert!(HttpProto::HTTP1_1.version() < (2, 0));
+        Ok(())
+    }
+
+    #[test]
+    fn epoch_should_work() -> Result<()> {
+        let s = r#"93.184.216.34 - - [17/May/2015:08:05:32 +0000] "GET / HTTP/1.1" 200 2 "-" "curl/8.0""#;
+        let log = parse_nginx_log(s).unwrap();
+        assert_eq!(log.epoch(), 1431849932);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_msec_should_work() -> Result<()> {
+        let mut s = "1431849932.123 ";
+        let dt = parse_msec(&mut s).unwrap();
+        assert_eq!(s, "");
+        assert_eq!(dt.timestamp(), 1431849932);
+        assert_eq!(dt.timestamp_subsec_millis(), 123);
+
+        let s = r#"93.184.216.34 - - 1431849932.5 "GET / HTTP/1.1" 200 2 "-" "curl/8.0""#;
+        let options = LogOptions {
+            time_format: TimeFormat::Msec,
+            ..Default::default()
+        };
+        let log = parse_nginx_log_with(s, options).unwrap();
+        assert_eq!(log.epoch(), 1431849932);
+        assert_eq!(log.datetime.timestamp_subsec_millis(), 500);
+        assert!(parse_nginx_log(s).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn parse_nginx_log_should_capture_remote_user() -> Result<()> {
+        let s = r#"93.184.216.34 - alice [07/Mar/2014:16:05:49 +0800] "GET /admin HTTP/1.1" 200 2 "-" "curl/8.0""#;
+        let log = parse_nginx_log(s).unwrap();
+        assert_eq!(log.ident, None);
+        assert_eq!(log.remote_user.as_deref(), Some("alice"));
+        assert_eq!(log.url, "/admin");
+
+        let s = r#"93.184.216.34 - - [07/Mar/2014:16:05:49 +0800] "GET / HTTP/1.1" 200 2 "-" "curl/8.0""#;
+        assert_eq!(parse_nginx_log(s).unwrap().remote_user, None);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_http_should_honor_escaped_quotes() -> Result<()> {
+        let mut s = r#""GET /search?q=\"x\" HTTP/1.1" 200"#;
+        let (method, url, protocol) = parse_http(&mut s, '"').unwrap();
+        assert_eq!(s, "200");
+        assert_eq!(method, HttpMethod::Get);
+        assert_eq!(url, r#"/search?q="x""#);
+        assert_eq!(protocol, HttpProto::HTTP1_1);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_http_should_allow_spaces_in_url() -> Result<()> {
+        let mut s = "\"GET /files/annual report.pdf HTTP/1.1\" 200";
+        let (method, url, protocol) = parse_http(&mut s, '"').unwrap();
+        assert_eq!(s, "200");
+        assert_eq!(method, HttpMethod::Get);
+        assert_eq!(url, "/files/annual report.pdf");
+        assert_eq!(protocol, HttpProto::HTTP1_1);
+
+        let mut s = "\"GET /a b  c\"";
+        let (_, url, protocol) = parse_http(&mut s, '"').unwrap();
+        assert_eq!(url, "/a b  c");
+        assert_eq!(protocol, HttpProto::HTTP0_9);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_http_should_default_to_http0_9() -> Result<()> {
+        let mut s = "\"GET /index.html\" 200";
+        let (method, url, protocol) = parse_http(&mut s, '"').unwrap();
+        assert_eq!(s, "200");
+        assert_eq!(method, HttpMethod::Get);
+        assert_eq!(url, "/index.html");
+        assert_eq!(protocol, HttpProto::HTTP0_9);
+
+        let mut s = "\"GET /index.html HTTP/9.9\"";
+        assert!(parse_http(&mut s, '"').is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn parse_http_should_accept_asterisk_form() -> Result<()> {
+        let s = r#"93.184.216.34 - - [07/Mar/2014:16:05:49 +0800] "OPTIONS * HTTP/1.1" 204 0 "-" "curl/8.0""#;
+        let log = parse_nginx_log(s).unwrap();
+        assert_eq!(log.method, HttpMethod::Options);
+        assert_eq!(log.url, "*");
+        assert_eq!(log.target_form(), TargetForm::Asterisk);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_http_should_accept_authority_form() -> Result<()> {
+        let s = r#"93.184.216.34 - - [07/Mar/2014:16:05:49 +0800] "CONNECT example.com:443 HTTP/1.1" 200 0 "-" "curl/8.0""#;
+        let log = parse_nginx_log(s).unwrap();
+        assert_eq!(log.method, HttpMethod::Connect);
+        assert_eq!(log.url, "example.com:443");
+        assert_eq!(log.target_form(), TargetForm::Authority);
+
+        let mut s = "\"CONNECT /tunnel HTTP/1.1\"";
+        assert!(parse_http(&mut s, '"').is_err());
+        let mut s = "\"CONNECT example.com HTTP/1.1\"";
+        assert!(parse_http(&mut s, '"').is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn connect_target_should_work() -> Result<()> {
+        let log = NginxLogBuilder::new()
+            .method(HttpMethod::Connect)
+            .url("example.com:443")
+            .build();
+        assert_eq!(log.connect_target()?, ("example.com".to_string(), 443));
+
+        let log = NginxLogBuilder::new()
+            .method(HttpMethod::Connect)
+            .url("example.com")
+            .build();
+        assert!(log.connect_target().is_err());
+        let log = NginxLogBuilder::new()
+            .method(HttpMethod::Connect)
+            .url("example.com:99999")
+            .build();
+        assert!(log.connect_target().is_err());
+        let log = NginxLogBuilder::new().url("/").build();
+        assert!(log.connect_target().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn parse_body_bytes_should_accept_markers() -> Result<()> {
+        let mut s = "1024 \"-\"";
+        assert_eq!(parse_body_bytes(&mut s).unwrap(), BodyBytes::Bytes(1024));
+        assert_eq!(s, "\"-\"");
+
+        let mut s = "chunked";
+        assert_eq!(parse_body_bytes(&mut s).unwrap(), BodyBytes::Chunked);
+
+        let s = r#"93.184.216.34 - - [07/Mar/2014:16:05:49 +0800] "GET /stream HTTP/1.1" 200 - "-" "curl/8.0""#;
+        let log = parse_nginx_log(s).unwrap();
+        assert_eq!(log.body_bytes, BodyBytes::Unknown);
+        assert_eq!(log.body_bytes.as_u64(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_body_bytes_should_reject_invalid_sizes() -> Result<()> {
+        let mut s = "-5 \"-\"";
+        let err = parse_body_bytes(&mut s).unwrap_err().into_inner().unwrap();
+        assert!(
+            err.to_string().contains("invalid body size `-5`: negative"),
+            "{}",
+            err
+        );
+
+        let mut s = "12kb";
+        let err = parse_body_bytes(&mut s).unwrap_err().into_inner().unwrap();
+        assert!(err.to_string().contains("not a number"), "{}", err);
+
+        let mut s = "- \"-\"";
+        assert_eq!(parse_body_bytes(&mut s).unwrap(), BodyBytes::Unknown);
+        let mut s = "0";
+        assert_eq!(parse_body_bytes(&mut s).unwrap(), BodyBytes::Bytes(0));
+        let mut s = "512";
+        assert_eq!(parse_body_bytes(&mut s).unwrap(), BodyBytes::Bytes(512));
+        Ok(())
+    }
+
+    #[test]
+    fn parse_quoted_string_should_unescape() -> Result<()> {
+        let mut s = r#""say \"hi\" C:\\dir\x22""#;
+        let ret = parse_quoted_string(&mut s, '"').unwrap();
+        assert_eq!(s, "");
+        assert_eq!(ret, r#"say "hi" C:\dir\x22"#);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_nginx_log_should_accept_single_quotes() -> Result<()> {
+        let s = r#"93.184.216.34 - - [07/Mar/2014:16:05:49 +0800] 'GET /search?q=it\'s HTTP/1.1' 200 2 '-' 'Mozilla/5.0 (X11; "Linux")'"#;
+        let options = LogOptions {
+            quote: '\'',
+            ..Default::default()
+        };
+        let log = parse_nginx_log_with(s, options).unwrap();
+        assert_eq!(log.method, HttpMethod::Get);
+        assert_eq!(log.url, "/search?q=it's");
+        assert_eq!(log.referer.as_deref(), Some("-"));
+        assert_eq!(
+            log.user_agent.as_deref(),
+            Some(r#"Mozilla/5.0 (X11; "Linux")"#)
+        );
+        assert!(parse_nginx_log(s).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn parse_http_should_work() -> Result<()> {
+        let mut s = "\"GET /download/product_1 HTTP/1.1\"";
+        let (method, url, protocol) = parse_http(&mut s, '"').unwrap();
+        assert_eq!(s, "");
+        assert_eq!(method, HttpMethod::Get);
+        assert_eq!(url, "/download/product_1");
+        assert_eq!(protocol, HttpProto::HTTP1_1);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_http_should_fold_method_case() -> Result<()> {
+        let mut s = "\"get /index.html HTTP/1.1\"";
+        let (method, url, _) = parse_http(&mut s, '"').unwrap();
+        assert_eq!(method, HttpMethod::Get);
+        assert_eq!(url, "/index.html");
+        Ok(())
+    }
+
+    #[test]
+    fn parse_http_should_split_method_glued_to_url() -> Result<()> {
+        let mut s = "\"GET/path HTTP/1.1\"";
+        let (method, url, protocol) = parse_http(&mut s, '"').unwrap();
+        assert_eq!(method, HttpMethod::Get);
+        assert_eq!(url, "/path");
+        assert_eq!(protocol, HttpProto::HTTP1_1);
+
+        let mut s = "\"post/api/v1/user?id=1 HTTP/1.0\"";
+        let (method, url, _) = parse_http(&mut s, '"').unwrap();
+        assert_eq!(method, HttpMethod::Post);
+        assert_eq!(url, "/api/v1/user?id=1");
+
+        for s in ["\"PropFind/calendar HTTP/1.1\"", "\"GET?x=1 HTTP/1.1\""] {
+            assert!(parse_http(&mut &*s, '"').is_err(), "{}", s);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn parse_http_should_keep_unknown_method() -> Result<()> {
+        let mut s = "\"PropFind /calendar HTTP/1.1\"";
+        let (method, url, _) = parse_http(&mut s, '"').unwrap();
+        assert_eq!(method, HttpMethod::Other("PropFind".to_string()));
+        assert_eq!(method.to_string(), "PropFind");
+        assert_eq!(url, "/calendar");
+        Ok(())
+    }
+
+    #[test]
+    fn parse_nginx_log_should_skip_leading_whitespace() -> Result<()> {
+        let s = r#"  	93.184.216.34 - - [07/Mar/2014:16:05:49 +0800] "GET /api/v1/user/login HTTP/1.1" 200 2 "-" "curl/8.0""#;
+        let log = parse_nginx_log(s).unwrap();
+        assert_eq!(log.addr, IpAddr::V4(Ipv4Addr::new(93, 184, 216, 34)));
+        assert_eq!(log.url, "/api/v1/user/login");
+        assert_eq!(log.user_agent.as_deref(), Some("curl/8.0"));
+        Ok(())
+    }
+
+    #[test]
+    fn parse_nginx_log_should_allow_missing_referer_and_user_agent() -> Result<()> {
+        let s =
+            r#"93.184.216.34 - - [07/Mar/2014:16:05:49 +0800] "GET /index.html HTTP/1.1" 200 512"#;
+        let log = parse_nginx_log(s).unwrap();
+        assert_eq!(log.body_bytes, BodyBytes::Bytes(512));
+        assert_eq!(log.referer, None);
+        assert_eq!(log.user_agent, None);
+        assert_eq!(log.request_length, None);
+
+        let strict = LogOptions {
+            strict: true,
+            ..Default::default()
+        };
+        let s = "93.184.216.34 - - [07/Mar/2014:16:05:49 +0800] \"GET / HTTP/1.1\" 200 2 \"-\"\n";
+        let log = parse_nginx_log_with(s, strict).unwrap();
+        assert_eq!(log.referer.as_deref(), Some("-"));
+        assert_eq!(log.user_agent, None);
+
+        let s = r#"93.184.216.34 - - [07/Mar/2014:16:05:49 +0800] "GET / HTTP/1.1" 200 2 garbage"#;
+        assert!(parse_nginx_log(s).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn parse_nginx_log_should_read_request_time() -> Result<()> {
+        let s = r#"93.184.216.34 - - [07/Mar/2014:16:05:49 +0800] "GET / HTTP/1.1" 200 2 "-" "curl/8.0" [0.123]"#;
+        let log = parse_nginx_log(s).unwrap();
+        assert_eq!(log.request_time, Some(0.123));
+        assert_eq!(log.extra, None);
+
+        let s = r#"93.184.216.34 - - [07/Mar/2014:16:05:49 +0800] "GET / HTTP/1.1" 200 2 "-" "curl/8.0" 4096 [2] 0.004 upstream"#;
+        let log = parse_nginx_log(s).unwrap();
+        assert_eq!(log.request_length, Some(4096));
+        assert_eq!(log.request_time, Some(2.0));
+        assert_eq!(log.extra.as_deref(), Some("0.004 upstream"));
+
+        let s = r#"93.184.216.34 - - [07/Mar/2014:16:05:49 +0800] "GET / HTTP/1.1" 200 2 "-" "curl/8.0" [-]"#;
+        let log = parse_nginx_log(s).unwrap();
+        assert_eq!(log.request_time, None);
+        assert_eq!(log.extra.as_deref(), Some("[-]"));
+        Ok(())
+    }
+
+    #[test]
+    fn parse_nginx_log_should_read_request_length() -> Result<()> {
+        let s = r#"93.184.216.34 - - [07/Mar/2014:16:05:49 +0800] "POST /api/v1/upload HTTP/1.1" 201 2 "-" "curl/8.0" 4096"#;
+        let log = parse_nginx_log(s).unwrap();
+        assert_eq!(log.body_bytes, BodyBytes::Bytes(2));
+        assert_eq!(log.request_length, Some(4096));
+
+        let s = r#"93.184.216.34 - - [07/Mar/2014:16:05:49 +0800] "GET / HTTP/1.1" 200 2 "-" "curl/8.0" -"#;
+        assert_eq!(parse_nginx_log(s).unwrap().request_length, None);
+
+        let s = r#"93.184.216.34 - - [07/Mar/2014:16:05:49 +0800] "GET / HTTP/1.1" 200 2 "-" "curl/8.0""#;
+        assert_eq!(parse_nginx_log(s).unwrap().request_length, None);
+
+        for field in ["0.004", "12abc", "-upstream", "0.004 [0.1]"] {
+            let s = format!(
+                r#"93.184.216.34 - - [07/Mar/2014:16:05:49 +0800] "GET / HTTP/1.1" 200 2 "-" "curl/8.0" {}"#,
+                field
+            );
+            let log = parse_nginx_log(&s).unwrap();
+            assert_eq!(log.request_length, None);
+            assert_eq!(log.request_time, None);
+            assert_eq!(log.extra.as_deref(), Some(field));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn parse_nginx_log_should_absorb_trailing_newline() -> Result<()> {
+        let strict = LogOptions {
+            strict: true,
+            ..Default::default()
+        };
+        let s = "93.184.216.34 - - [07/Mar/2014:16:05:49 +0800] \"GET / HTTP/1.1\" 200 2 \"-\" \"curl/8.0\"\n";
+        let log = parse_nginx_log_with(s, strict.clone()).unwrap();
+        assert_eq!(log.user_agent.as_deref(), Some("curl/8.0"));
+        assert_eq!(log.extra, None);
+
+        let s = "93.184.216.34 - - [07/Mar/2014:16:05:49 +0800] \"GET / HTTP/1.1\" 200 2 \"-\" \"curl/8.0\" 512\r\n";
+        let log = parse_nginx_log_with(s, strict).unwrap();
+        assert_eq!(log.request_length, Some(512));
+        Ok(())
+    }
+
+    #[test]
+    fn parse_nginx_log_should_keep_extra_fields() -> Result<()> {
+        let s = r#"93.184.216.34 - - [07/Mar/2014:16:05:49 +0800] "GET / HTTP/1.1" 200 2 "-" "curl/8.0" 512 rt=0.004 upstream="10.0.0.1:80""#;
+        let log = parse_nginx_log(s).unwrap();
+        assert_eq!(log.request_length, Some(512));
+        assert_eq!(
+            log.extra.as_deref(),
+            Some(r#"rt=0.004 upstream="10.0.0.1:80""#)
+        );
+
+        let strict = LogOptions {
+            strict: true,
+            ..Default::default()
+        };
+        assert!(parse_nginx_log_with(s, strict.clone()).is_err());
+
+        let s = r#"93.184.216.34 - - [07/Mar/2014:16:05:49 +0800] "GET / HTTP/1.1" 200 2 "-" "curl/8.0" 512"#;
+        assert_eq!(parse_nginx_log(s).unwrap().extra, None);
+        assert_eq!(parse_nginx_log_with(s, strict).unwrap().extra, None);
+        Ok(())
+    }
+
+    #[test]
+    fn summary_should_work() -> Result<()> {
+        let s = r#"93.184.216.34 - - [07/Mar/2014:16:05:49 +0800] "GET /api/v1/user/login HTTP/1.1" 404 153 "-" "curl/8.0""#;
+        let log = parse_nginx_log(s).unwrap();
+        assert_eq!(log.status_text(), "Not Found");
+        assert_eq!(
+            log.summary(),
+            "IP:         93.184.216.34\n\
+             Request:    GET /api/v1/user/login HTTP/1.1\n\
+             Status:     404 Not Found\n\
+             Bytes:      153\n\
+             User-Agent: curl/8.0"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn referer_host_and_path_should_work() -> Result<()> {
+        let mut log = NginxLogBuilder::new()
+            .referer("https://user@example.com:8443/page/1?x=1#top")
+            .build();
+        assert_eq!(log.referer_host(), Some("example.com"));
+        assert_eq!(log.referer_path(), Some("/page/1"));
+
+        log.referer = Some("http://[::1]:8080".to_string());
+        assert_eq!(log.referer_host(), Some("::1"));
+        assert_eq!(log.referer_path(), Some("/"));
+
+        for referer in ["-", "example.com/page", ""] {
+            log.referer = Some(referer.to_string());
+            assert_eq!(log.referer_host(), None, "{}", referer);
+            assert_eq!(log.referer_path(), None, "{}", referer);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn is_api_request_should_work() -> Result<()> {
+        let mut log = NginxLogBuilder::new().url("/api/v1/user/login").build();
+        assert!(log.is_api_request(&["/api/"]));
+        assert!(log.is_api_request(&["/graphql", "/api/v1/"]));
+        assert!(!log.is_api_request(&["/admin/"]));
+        assert!(!log.is_api_request(&[]));
+
+        log.url = "/apiary?next=/api/".to_string();
+        assert!(!log.is_api_request(&["/api/"]));
+        Ok(())
+    }
+
+    #[test]
+    fn referer_is_internal_should_work() -> Result<()> {
+        let mut log = NginxLogBuilder::new()
+            .referer("https://Example.com:8443/page")
+            .build();
+        assert!(log.referer_is_internal("example.com"));
+
+        log.referer = Some("https://search.example.org/?q=example.com".to_string());
+        assert!(!log.referer_is_internal("example.com"));
+
+        log.referer = Some("-".to_string());
+        assert!(!log.referer_is_internal("example.com"));
+        Ok(())
+    }
+
+    #[test]
+    fn try_from_str_should_work() -> Result<()> {
+        assert_eq!(HttpMethod::try_from("GET")?, HttpMethod::Get);
+        assert_eq!(HttpProto::try_from("HTTP/2.0")?, HttpProto::HTTP2_0);
+        assert_eq!(
+            HttpMethod::try_from("get")?,
+            HttpMethod::Other("get".to_string())
+        );
+        assert!(HttpMethod::try_from("GET /").is_err());
+        assert!(HttpMethod::try_from("").is_err());
+        assert!(HttpProto::try_from("HTTP/1.2").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn display_should_roundtrip() -> Result<()> {
+        for method in [
+            "GET", "POST", "PUT", "DELETE", "HEAD", "CONNECT", "OPTIONS", "TRACE", "PATCH",
+        ] {
+            assert_eq!(HttpMethod::try_from(method)?.to_string(), method);
+        }
+        let other = HttpMethod::Other("PROPFIND".to_string());
+        assert_eq!(other.to_string().parse::<HttpMethod>()?, other);
+        for proto in ["HTTP/0.9", "HTTP/1.0", "HTTP/1.1", "HTTP/2.0", "HTTP/3.0"] {
+            assert_eq!(HttpProto::try_from(proto)?.to_string(), proto);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,135 @@
+use super::{BodyBytes, HttpMethod, HttpProto, NginxLog};
+use chrono::{DateTime, Utc};
+use std::net::{IpAddr, Ipv4Addr};
+
+/// Builds a [`NginxLog`] field by field, starting from a plain
+/// `GET / HTTP/1.1` request from `127.0.0.1` at the Unix epoch answered with
+/// an empty `200`.
+#[derive(Debug)]
+pub struct NginxLogBuilder {
+    log: NginxLog,
+}
+
+impl NginxLogBuilder {
+    pub fn new() -> Self {
+        Self {
+            log: NginxLog {
+                addr: IpAddr::V4(Ipv4Addr::LOCALHOST),
+                ident: None,
+                remote_user: None,
+                datetime: DateTime::<Utc>::UNIX_EPOCH,
+                method: HttpMethod::Get,
+                url: "/".to_string(),
+                protocol: HttpProto::HTTP1_1,
+                status: 200,
+                body_bytes: BodyBytes::Bytes(0),
+                referer: Some("-".to_string()),
+                user_agent: Some("-".to_string()),
+                request_length: None,
+                request_time: None,
+                extra: None,
+            },
+        }
+    }
+
+    pub fn addr(mut self, addr: impl Into<IpAddr>) -> Self {
+        self.log.addr = addr.into();
+        self
+    }
+
+    pub fn ident(mut self, ident: impl Into<String>) -> Self {
+        self.log.ident = Some(ident.into());
+        self
+    }
+
+    pub fn remote_user(mut self, remote_user: impl Into<String>) -> Self {
+        self.log.remote_user = Some(remote_user.into());
+        self
+    }
+
+    pub fn datetime(mut self, datetime: DateTime<Utc>) -> Self {
+        self.log.datetime = datetime;
+        self
+    }
+
+    pub fn method(mut self, method: HttpMethod) -> Self {
+        self.log.method = method;
+        self
+    }
+
+    pub fn url(mut self, url: impl Into<String>) -> Self {
+        self.log.url = url.into();
+        self
+    }
+
+    pub fn protocol(mut self, protocol: HttpProto) -> Self {
+        self.log.protocol = protocol;
+        self
+    }
+
+    pub fn status(mut self, status: u16) -> Self {
+        self.log.status = status;
+        self
+    }
+
+    pub fn body_bytes(mut self, body_bytes: BodyBytes) -> Self {
+        self.log.body_bytes = body_bytes;
+        self
+    }
+
+    pub fn referer(mut self, referer: impl Into<String>) -> Self {
+        self.log.referer = Some(referer.into());
+        self
+    }
+
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.log.user_agent = Some(user_agent.into());
+        self
+    }
+
+    pub fn request_length(mut self, request_length: u64) -> Self {
+        self.log.request_length = Some(request_length);
+        self
+    }
+
+    pub fn request_time(mut self, request_time: f64) -> Self {
+        self.log.request_time = Some(request_time);
+        self
+    }
+
+    pub fn extra(mut self, extra: impl Into<String>) -> Self {
+        self.log.extra = Some(extra.into());
+        self
+    }
+
+    pub fn build(self) -> NginxLog {
+        self.log
+    }
+}
+
+impl Default for NginxLogBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builder_should_override_defaults() {
+        let log = NginxLogBuilder::new()
+            .status(404)
+            .url("/missing")
+            .addr(Ipv4Addr::new(10, 0, 0, 1))
+            .build();
+        assert_eq!(log.status, 404);
+        assert_eq!(log.url, "/missing");
+        assert_eq!(log.addr, IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)));
+        assert_eq!(log.method, HttpMethod::Get);
+        assert_eq!(log.protocol, HttpProto::HTTP1_1);
+        assert_eq!(log.body_bytes, BodyBytes::Bytes(0));
+        assert_eq!(log.epoch(), 0);
+    }
+}
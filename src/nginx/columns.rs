@@ -0,0 +1,80 @@
+use super::{BodyBytes, HttpMethod, HttpProto, NginxLog};
+use chrono::{DateTime, Utc};
+use std::net::IpAddr;
+
+/// The fields of a batch of logs stored column by column: the values at the
+/// same index of every column belong to the same log.
+#[derive(Debug, Clone, Default)]
+pub struct LogColumns {
+    pub addr: Vec<IpAddr>,
+    pub ident: Vec<Option<String>>,
+    pub remote_user: Vec<Option<String>>,
+    pub datetime: Vec<DateTime<Utc>>,
+    pub method: Vec<HttpMethod>,
+    pub url: Vec<String>,
+    pub protocol: Vec<HttpProto>,
+    pub status: Vec<u16>,
+    pub body_bytes: Vec<BodyBytes>,
+    pub referer: Vec<Option<String>>,
+    pub user_agent: Vec<Option<String>>,
+    pub request_length: Vec<Option<u64>>,
+    pub request_time: Vec<Option<f64>>,
+    pub extra: Vec<Option<String>>,
+}
+
+impl LogColumns {
+    /// The number of logs, i.e. the length of every column.
+    pub fn len(&self) -> usize {
+        self.status.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.status.is_empty()
+    }
+
+    pub fn push(&mut self, log: &NginxLog) {
+        self.addr.push(log.addr);
+        self.ident.push(log.ident.clone());
+        self.remote_user.push(log.remote_user.clone());
+        self.datetime.push(log.datetime);
+        self.method.push(log.method.clone());
+        self.url.push(log.url.clone());
+        self.protocol.push(log.protocol.clone());
+        self.status.push(log.status);
+        self.body_bytes.push(log.body_bytes);
+        self.referer.push(log.referer.clone());
+        self.user_agent.push(log.user_agent.clone());
+        self.request_length.push(log.request_length);
+        self.request_time.push(log.request_time);
+        self.extra.push(log.extra.clone());
+    }
+}
+
+impl From<&[NginxLog]> for LogColumns {
+    fn from(logs: &[NginxLog]) -> Self {
+        let mut columns = LogColumns::default();
+        logs.iter().for_each(|log| columns.push(log));
+        columns
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nginx::NginxLogBuilder;
+
+    #[test]
+    fn log_columns_should_keep_rows_aligned() {
+        let logs = [
+            NginxLogBuilder::new().status(200).url("/a").build(),
+            NginxLogBuilder::new().status(404).url("/b").build(),
+            NginxLogBuilder::new().status(500).url("/c").build(),
+        ];
+        let columns = LogColumns::from(&logs[..]);
+        assert_eq!(columns.len(), 3);
+        assert_eq!(columns.status, [200, 404, 500]);
+        assert_eq!(columns.url[1], "/b");
+        let errors = columns.status.iter().filter(|&&s| s >= 500).count();
+        assert_eq!(errors, 1);
+    }
+}
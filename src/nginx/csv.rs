@@ -0,0 +1,102 @@
+use super::NginxLog;
+use std::io::{self, Write};
+
+const HEADER: [&str; 14] = [
+    "addr",
+    "ident",
+    "remote_user",
+    "datetime",
+    "method",
+    "url",
+    "protocol",
+    "status",
+    "body_bytes",
+    "referer",
+    "user_agent",
+    "request_length",
+    "request_time",
+    "extra",
+];
+
+/// Writes the logs as CSV (RFC 4180): a header row, then one row per log with
+/// the fields in the order they appear in a log line. Times are in RFC 3339,
+/// missing optional fields are empty, and fields with commas, quotes or line
+/// breaks are quoted.
+pub fn logs_to_csv<W: Write>(logs: &[NginxLog], w: &mut W) -> io::Result<()> {
+    write_row(w, HEADER)?;
+    for log in logs {
+        write_row(
+            w,
+            [
+                log.addr.to_string(),
+                optional(&log.ident),
+                optional(&log.remote_user),
+                log.datetime.to_rfc3339(),
+                log.method.to_string(),
+                log.url.clone(),
+                log.protocol.to_string(),
+                log.status.to_string(),
+                log.body_bytes.to_string(),
+                optional(&log.referer),
+                optional(&log.user_agent),
+                optional(&log.request_length),
+                optional(&log.request_time),
+                optional(&log.extra),
+            ],
+        )?;
+    }
+    Ok(())
+}
+
+fn write_row<W: Write>(w: &mut W, fields: [impl AsRef<str>; 14]) -> io::Result<()> {
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            w.write_all(b",")?;
+        }
+        let field = field.as_ref();
+        if field.contains([',', '"', '\r', '\n']) {
+            write!(w, "\"{}\"", field.replace('"', "\"\""))?;
+        } else {
+            w.write_all(field.as_bytes())?;
+        }
+    }
+    w.write_all(b"\r\n")
+}
+
+fn optional<T: ToString>(value: &Option<T>) -> String {
+    value.as_ref().map_or_else(String::new, T::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nginx::{parse_nginx_log, BodyBytes, HttpMethod, NginxLogBuilder};
+    use anyhow::Result;
+
+    #[test]
+    fn logs_to_csv_should_work() -> Result<()> {
+        let first = parse_nginx_log(
+            r#"93.184.216.34 - alice [07/Mar/2014:16:05:49 +0800] "GET /search?q=a,b HTTP/1.1" 200 2 "-" "Mozilla/5.0 (X11; Linux x86_64) \"Gecko\", like Safari""#,
+        )
+        .unwrap();
+        let second = NginxLogBuilder::new()
+            .method(HttpMethod::Post)
+            .url("/api")
+            .status(201)
+            .body_bytes(BodyBytes::Chunked)
+            .build();
+        let mut out = Vec::new();
+        logs_to_csv(&[first, second], &mut out)?;
+        assert_eq!(
+            String::from_utf8(out)?,
+            "addr,ident,remote_user,datetime,method,url,protocol,status,body_bytes,referer,user_agent,request_length,request_time,extra\r\n\
+             93.184.216.34,,alice,2014-03-07T08:05:49+00:00,GET,\"/search?q=a,b\",HTTP/1.1,200,2,-,\"Mozilla/5.0 (X11; Linux x86_64) \"\"Gecko\"\", like Safari\",,,\r\n\
+             127.0.0.1,,,1970-01-01T00:00:00+00:00,POST,/api,HTTP/1.1,201,chunked,-,-,,,\r\n"
+        );
+
+        let mut out = Vec::new();
+        logs_to_csv(&[], &mut out)?;
+        assert_eq!(out.iter().filter(|&&b| b == b'\n').count(), 1);
+        Ok(())
+    }
+}
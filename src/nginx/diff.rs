@@ -0,0 +1,102 @@
+use super::NginxLog;
+use std::fmt;
+
+/// One field that differs between two logs, rendered as text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldDiff {
+    pub field: &'static str,
+    pub left: String,
+    pub right: String,
+}
+
+impl fmt::Display for FieldDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} -> {}", self.field, self.left, self.right)
+    }
+}
+
+impl NginxLog {
+    /// Compares the two logs field by field and returns the fields that differ,
+    /// in the order they appear in a log line. Equal fields are left out.
+    pub fn diff(&self, other: &NginxLog) -> Vec<FieldDiff> {
+        let mut diffs = Vec::new();
+        let mut compare = |field, left: String, right: String| {
+            if left != right {
+                diffs.push(FieldDiff { field, left, right });
+            }
+        };
+        compare("addr", self.addr.to_string(), other.addr.to_string());
+        compare("ident", optional(&self.ident), optional(&other.ident));
+        compare(
+            "remote_user",
+            optional(&self.remote_user),
+            optional(&other.remote_user),
+        );
+        compare(
+            "datetime",
+            self.datetime.to_rfc3339(),
+            other.datetime.to_rfc3339(),
+        );
+        compare("method", self.method.to_string(), other.method.to_string());
+        compare("url", self.url.clone(), other.url.clone());
+        compare(
+            "protocol",
+            self.protocol.to_string(),
+            other.protocol.to_string(),
+        );
+        compare("status", self.status.to_string(), other.status.to_string());
+        compare(
+            "body_bytes",
+            self.body_bytes.to_string(),
+            other.body_bytes.to_string(),
+        );
+        compare("referer", optional(&self.referer), optional(&other.referer));
+        compare(
+            "user_agent",
+            optional(&self.user_agent),
+            optional(&other.user_agent),
+        );
+        compare(
+            "request_length",
+            optional(&self.request_length),
+            optional(&other.request_length),
+        );
+        compare(
+            "request_time",
+            optional(&self.request_time),
+            optional(&other.request_time),
+        );
+        compare("extra", optional(&self.extra), optional(&other.extra));
+        diffs
+    }
+}
+
+fn optional<T: ToString>(value: &Option<T>) -> String {
+    value.as_ref().map_or_else(|| "-".to_string(), T::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nginx::parse_nginx_log;
+    use anyhow::Result;
+
+    #[test]
+    fn diff_should_only_report_changed_fields() -> Result<()> {
+        let before = parse_nginx_log(r#"93.184.216.34 - - [07/Mar/2014:16:05:49 +0800] "GET /api/v1/user HTTP/1.1" 200 15 "-" "curl/8.0""#).unwrap();
+        let after = parse_nginx_log(r#"93.184.216.34 - - [07/Mar/2014:16:05:49 +0800] "GET /api/v1/user HTTP/1.1" 503 15 "-" "curl/8.0""#).unwrap();
+
+        let diffs = before.diff(&after);
+        assert_eq!(
+            diffs,
+            [FieldDiff {
+                field: "status",
+                left: "200".to_string(),
+                right: "503".to_string(),
+            }]
+        );
+        assert_eq!(diffs[0].to_string(), "status: 200 -> 503");
+        assert!(before.diff(&before).is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,69 @@
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+
+/// Names for the columns of a log whose fields are separated by whitespace
+/// and never quoted, for simple custom formats that
+/// [`parse_nginx_log`](super::parse_nginx_log) does not cover. Columns are
+/// counted from 0; unnamed ones are skipped.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FieldMap {
+    columns: Vec<(usize, String)>,
+}
+
+impl FieldMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Names the column at `index`.
+    pub fn column(mut self, index: usize, name: impl Into<String>) -> Self {
+        self.columns.push((index, name.into()));
+        self
+    }
+}
+
+/// Splits `line` on whitespace and returns the named columns of `map` by
+/// name. Fails if the line has fewer columns than `map` names.
+pub fn parse_with_fieldmap(line: &str, map: &FieldMap) -> Result<HashMap<String, String>> {
+    let columns: Vec<_> = line.split_whitespace().collect();
+    map.columns
+        .iter()
+        .map(|(index, name)| {
+            let value = columns.get(*index).ok_or_else(|| {
+                anyhow!(
+                    "missing column {} ({}): the line has {} columns",
+                    index,
+                    name,
+                    columns.len()
+                )
+            })?;
+            Ok((name.clone(), value.to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_with_fieldmap_should_work() -> Result<()> {
+        let map = FieldMap::new()
+            .column(0, "addr")
+            .column(3, "path")
+            .column(6, "time");
+        let fields = parse_with_fieldmap("10.0.0.1 - GET /index.html 200 512 0.004\n", &map)?;
+        assert_eq!(fields.len(), 3);
+        assert_eq!(fields["addr"], "10.0.0.1");
+        assert_eq!(fields["path"], "/index.html");
+        assert_eq!(fields["time"], "0.004");
+
+        let err = parse_with_fieldmap("10.0.0.1  -\tGET /", &map).unwrap_err();
+        assert!(
+            err.to_string().contains("missing column 6 (time)"),
+            "{}",
+            err
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,189 @@
+use super::{parse_nginx_log, NginxLog};
+use anyhow::{anyhow, Result};
+#[cfg(feature = "flate2")]
+use flate2::read::MultiGzDecoder;
+use std::io::BufRead;
+#[cfg(feature = "flate2")]
+use std::io::{BufReader, Read};
+
+/// Parses a batch of log lines, skipping blank ones. Both `\n` and `\r\n`
+/// line endings are accepted.
+pub fn parse_nginx_logs(s: &str) -> Result<Vec<NginxLog>> {
+    parse_nginx_logs_delimited(s, '\n')
+}
+
+/// Like [`parse_nginx_logs`], for records joined with `delimiter` instead of
+/// newlines, e.g. `\0`. Errors count the records from 1 as if they were
+/// lines.
+pub fn parse_nginx_logs_delimited(s: &str, delimiter: char) -> Result<Vec<NginxLog>> {
+    s.split(delimiter)
+        .enumerate()
+        .map(|(i, line)| (i + 1, trim_line_ending(line)))
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(n, line)| parse_line(n, line))
+        .collect()
+}
+
+/// Iterates over the parsed logs of a reader, one per non-blank line.
+#[derive(Debug)]
+pub struct LogLines<R> {
+    reader: R,
+    buf: String,
+    line: usize,
+    tailing: bool,
+}
+
+impl<R: BufRead> LogLines<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buf: String::new(),
+            line: 0,
+            tailing: false,
+        }
+    }
+
+    /// For a log that is still being written to: a last line without a
+    /// newline is taken as incomplete and held back instead of failing to
+    /// parse. The iterator then ends, and calling `next` again once the
+    /// reader has more data picks the line up where it was cut.
+    pub fn tailing(mut self) -> Self {
+        self.tailing = true;
+        self
+    }
+}
+
+impl<R: BufRead> Iterator for LogLines<R> {
+    type Item = Result<NginxLog>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            // a held back line is completed by the next read
+            if !self.tailing || self.buf.ends_with('\n') {
+                self.buf.clear();
+            }
+            match self.reader.read_line(&mut self.buf) {
+                Ok(0) => return None,
+                Ok(_) if self.tailing && !self.buf.ends_with('\n') => return None,
+                Ok(_) => self.line += 1,
+                Err(e) => return Some(Err(e.into())),
+            }
+            let line = trim_line_ending(&self.buf);
+            if !line.trim().is_empty() {
+                return Some(parse_line(self.line, line));
+            }
+        }
+    }
+}
+
+/// Iterates over the parsed logs of a gzip-compressed log such as a rotated
+/// `access.log.gz`, decompressing it as it goes. Files made of several
+/// concatenated gzip members are read through to the end.
+#[cfg(feature = "flate2")]
+pub fn parse_nginx_log_gz<R: Read>(r: R) -> LogLines<BufReader<MultiGzDecoder<R>>> {
+    LogLines::new(BufReader::new(MultiGzDecoder::new(r)))
+}
+
+// files moved between platforms may end their lines with `\r\n`
+fn trim_line_ending(line: &str) -> &str {
+    let line = line.strip_suffix('\n').unwrap_or(line);
+    line.strip_suffix('\r').unwrap_or(line)
+}
+
+fn parse_line(n: usize, line: &str) -> Result<NginxLog> {
+    parse_nginx_log(line).map_err(|e| anyhow!("Failed to parse log at line {}: {:?}", n, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        cell::RefCell,
+        io::{BufReader, Cursor, Read},
+        rc::Rc,
+    };
+
+    const CRLF_LOGS: &str = "93.184.216.34 - - [07/Mar/2014:16:05:49 +0800] \"GET /api/v1/user/login HTTP/1.1\" 200 2 \"-\" \"curl/8.0\"\r\n\
+        \r\n\
+        10.0.0.1 - - [07/Mar/2014:16:06:00 +0800] \"POST /api/v1/user HTTP/1.1\" 201 15 \"-\" \"curl/8.0\"\r\n";
+
+    #[test]
+    fn parse_nginx_logs_should_handle_crlf() -> Result<()> {
+        let logs = parse_nginx_logs(CRLF_LOGS)?;
+        assert_eq!(logs.len(), 2);
+        assert_eq!(logs[0].user_agent.as_deref(), Some("curl/8.0"));
+        assert_eq!(logs[1].status, 201);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_nginx_logs_delimited_should_work() -> Result<()> {
+        let logs = CRLF_LOGS.replace("\r\n", "\0");
+        let logs = parse_nginx_logs_delimited(&logs, '\0')?;
+        assert_eq!(logs.len(), 2);
+        assert_eq!(logs[0].user_agent.as_deref(), Some("curl/8.0"));
+        assert_eq!(logs[1].status, 201);
+
+        let err = parse_nginx_logs_delimited("\0garbage", '\0').unwrap_err();
+        assert!(err.to_string().contains("line 2"), "{}", err);
+        Ok(())
+    }
+
+    #[test]
+    fn log_lines_should_handle_crlf() -> Result<()> {
+        let logs = LogLines::new(Cursor::new(CRLF_LOGS)).collect::<Result<Vec<_>>>()?;
+        assert_eq!(logs.len(), 2);
+        assert_eq!(logs[0].user_agent.as_deref(), Some("curl/8.0"));
+        assert_eq!(logs[1].url, "/api/v1/user");
+        Ok(())
+    }
+
+    // a file that another handle keeps appending to
+    struct Growing(Rc<RefCell<Vec<u8>>>, usize);
+
+    impl Read for Growing {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let data = self.0.borrow();
+            let n = (&data[self.1..]).read(buf)?;
+            self.1 += n;
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn log_lines_should_hold_back_partial_line_when_tailing() -> Result<()> {
+        let (first, second) = CRLF_LOGS.split_at(CRLF_LOGS.len() - 40);
+        let file = Rc::new(RefCell::new(first.as_bytes().to_vec()));
+        let mut logs = LogLines::new(BufReader::new(Growing(file.clone(), 0))).tailing();
+        assert_eq!(logs.next().unwrap()?.url, "/api/v1/user/login");
+        assert!(logs.next().is_none());
+
+        file.borrow_mut().extend_from_slice(second.as_bytes());
+        let log = logs.next().unwrap()?;
+        assert_eq!(log.url, "/api/v1/user");
+        assert_eq!(log.user_agent.as_deref(), Some("curl/8.0"));
+        assert!(logs.next().is_none());
+
+        // without tailing the cut line is an error
+        let mut logs = LogLines::new(Cursor::new(first));
+        assert!(logs.next().unwrap().is_ok());
+        assert!(logs.next().unwrap().is_err());
+        Ok(())
+    }
+
+    #[cfg(feature = "flate2")]
+    #[test]
+    fn parse_nginx_log_gz_should_work() -> Result<()> {
+        use flate2::{write::GzEncoder, Compression};
+        use std::io::Write;
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(CRLF_LOGS.as_bytes())?;
+        let gz = encoder.finish()?;
+        let logs = parse_nginx_log_gz(gz.as_slice()).collect::<Result<Vec<_>>>()?;
+        assert_eq!(logs.len(), 2);
+        assert_eq!(logs[0].url, "/api/v1/user/login");
+        assert_eq!(logs[1].status, 201);
+        Ok(())
+    }
+}
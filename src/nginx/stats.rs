@@ -0,0 +1,101 @@
+use super::NginxLog;
+use std::collections::{BTreeMap, HashMap};
+
+/// Sums the body bytes sent per request path, without query or fragment.
+/// Logs whose size is chunked or unknown add nothing.
+pub fn bytes_by_url(logs: &[NginxLog]) -> HashMap<String, u64> {
+    let mut bytes = HashMap::new();
+    for log in logs {
+        *bytes.entry(path_of(&log.url).to_string()).or_default() +=
+            log.body_bytes.as_u64().unwrap_or(0);
+    }
+    bytes
+}
+
+/// The `n` most requested paths, without query or fragment, with their
+/// request counts. Sorted by descending count, then by path.
+pub fn top_urls(logs: &[NginxLog], n: usize) -> Vec<(String, usize)> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for log in logs {
+        *counts.entry(path_of(&log.url)).or_default() += 1;
+    }
+    let mut top: Vec<_> = counts.into_iter().collect();
+    top.sort_unstable_by(|(a, m), (b, n)| n.cmp(m).then(a.cmp(b)));
+    top.into_iter()
+        .take(n)
+        .map(|(path, count)| (path.to_string(), count))
+        .collect()
+}
+
+/// Counts the logs per status code, in ascending order of status.
+pub fn status_histogram(logs: &[NginxLog]) -> BTreeMap<u16, usize> {
+    let mut histogram = BTreeMap::new();
+    for log in logs {
+        *histogram.entry(log.status).or_default() += 1;
+    }
+    histogram
+}
+
+pub(super) fn path_of(url: &str) -> &str {
+    url.split(['?', '#']).next().unwrap_or(url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nginx::{BodyBytes, NginxLogBuilder};
+    use anyhow::Result;
+
+    fn log(url: &str, body_bytes: BodyBytes) -> NginxLog {
+        NginxLogBuilder::new()
+            .url(url)
+            .body_bytes(body_bytes)
+            .build()
+    }
+
+    #[test]
+    fn bytes_by_url_should_work() -> Result<()> {
+        let logs = [
+            log("/index.html", BodyBytes::Bytes(100)),
+            log("/api/users?page=2", BodyBytes::Bytes(20)),
+            log("/index.html#top", BodyBytes::Bytes(50)),
+            log("/api/users", BodyBytes::Chunked),
+            log("/api/users?page=3", BodyBytes::Bytes(22)),
+        ];
+        let bytes = bytes_by_url(&logs);
+        assert_eq!(bytes.len(), 2);
+        assert_eq!(bytes["/index.html"], 150);
+        assert_eq!(bytes["/api/users"], 42);
+        Ok(())
+    }
+
+    #[test]
+    fn top_urls_should_work() -> Result<()> {
+        let logs: Vec<_> = ["/b", "/a?x=1", "/c", "/a", "/c#top", "/b", "/a"]
+            .into_iter()
+            .map(|url| log(url, BodyBytes::Unknown))
+            .collect();
+        assert_eq!(
+            top_urls(&logs, 2),
+            [("/a".to_string(), 3), ("/b".to_string(), 2)]
+        );
+        assert_eq!(top_urls(&logs, 10).len(), 3);
+        assert!(top_urls(&logs, 0).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn status_histogram_should_work() -> Result<()> {
+        let logs: Vec<_> = [200, 404, 200, 500, 304, 200, 404]
+            .into_iter()
+            .map(|status| NginxLogBuilder::new().status(status).build())
+            .collect();
+        let histogram = status_histogram(&logs);
+        assert_eq!(
+            histogram.into_iter().collect::<Vec<_>>(),
+            [(200, 3), (304, 1), (404, 2), (500, 1)]
+        );
+        assert!(status_histogram(&[]).is_empty());
+        Ok(())
+    }
+}
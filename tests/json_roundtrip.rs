@@ -0,0 +1,35 @@
+use proptest::prelude::*;
+use std::sync::Arc;
+use template::json::{parse_json, JsonValue, Num};
+
+fn arb_json() -> impl Strategy<Value = JsonValue> {
+    // NaN and the infinities have no JSON representation
+    let finite = prop::num::f64::POSITIVE
+        | prop::num::f64::NEGATIVE
+        | prop::num::f64::NORMAL
+        | prop::num::f64::SUBNORMAL
+        | prop::num::f64::ZERO;
+    let leaf = prop_oneof![
+        Just(JsonValue::Null),
+        any::<bool>().prop_map(JsonValue::Bool),
+        any::<i64>().prop_map(|n| JsonValue::Number(Num::Int(n))),
+        finite.prop_map(|n| JsonValue::Number(Num::Float(n))),
+        any::<String>().prop_map(JsonValue::String),
+    ];
+    leaf.prop_recursive(4, 64, 8, |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..8).prop_map(JsonValue::Array),
+            prop::collection::hash_map(any::<String>().prop_map(Arc::from), inner, 0..8)
+                .prop_map(JsonValue::Object),
+        ]
+    })
+}
+
+proptest! {
+    #[test]
+    fn display_then_parse_should_roundtrip(value in arb_json()) {
+        let s = value.to_string();
+        let parsed = parse_json(&s).unwrap();
+        prop_assert_eq!(parsed, value);
+    }
+}